@@ -0,0 +1,22 @@
+use super::*;
+
+pub const SPEED_FACTOR: f32 = 1.5;
+pub const AIRTIME_FACTOR: f32 = 1.5;
+pub const GROWTH_FACTOR: f32 = 0.5;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Ability {
+    Speed,
+    Airtime,
+    Growth,
+}
+
+impl Ability {
+    pub fn text(&self) -> &'static str {
+        match self {
+            Self::Speed => "ability.speed",
+            Self::Airtime => "ability.airtime",
+            Self::Growth => "ability.growth",
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use super::*;
+
+// geng can't report when a web sound ends or pan it, so web voices are
+// counted for a fixed time and always play centered.
+#[cfg(target_arch = "wasm32")]
+const WEB_VOICE_TIME: f64 = 0.5;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SoundCategory {
+    Sfx,
+    Ui,
+}
+
+enum Voice {
+    #[cfg(not(target_arch = "wasm32"))]
+    Native(rodio::Sink),
+    #[cfg(target_arch = "wasm32")]
+    Web(f64),
+}
+
+impl Voice {
+    #[cfg_attr(not(target_arch = "wasm32"), allow(unused_variables))]
+    fn playing(&self, time: f64) -> bool {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Native(sink) => !sink.empty(),
+            #[cfg(target_arch = "wasm32")]
+            Self::Web(end) => time < *end,
+        }
+    }
+}
+
+// Native builds decode and pan the raw files themselves, so geng's own audio
+// (and its output stream) is only enabled on the web.
+#[cfg(not(target_arch = "wasm32"))]
+type SfxSound = std::sync::Arc<[u8]>;
+#[cfg(target_arch = "wasm32")]
+type SfxSound = geng::Sound;
+
+pub struct SfxData {
+    sounds: HashMap<Sfx, SfxSound>,
+}
+
+impl geng::LoadAsset for SfxData {
+    fn load(geng: &Geng, path: &str) -> geng::AssetFuture<Self> {
+        #[cfg(not(target_arch = "wasm32"))]
+        type File = Vec<u8>;
+        #[cfg(target_arch = "wasm32")]
+        type File = geng::Sound;
+        let files = future::try_join_all(Sfx::ALL.iter().map(|sfx| {
+            <File as geng::LoadAsset>::load(geng, &format!("{}/{}", path, sfx.file_name()))
+        }));
+        Box::pin(async move {
+            let sounds = Sfx::ALL
+                .iter()
+                .copied()
+                .zip(files.await?.into_iter().map(SfxSound::from))
+                .collect();
+            Ok(Self { sounds })
+        })
+    }
+    const DEFAULT_EXT: Option<&'static str> = None;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct NativeAudio {
+    handle: rodio::OutputStreamHandle,
+    _stream: std::sync::mpsc::Sender<()>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl NativeAudio {
+    fn new() -> Option<Self> {
+        let (handle_sender, handle_receiver) = std::sync::mpsc::channel();
+        let (stream, stream_receiver) = std::sync::mpsc::channel::<()>();
+        // The stream can't leave the thread that opened it, so that thread keeps it
+        // until the mixer is dropped.
+        std::thread::spawn(move || {
+            if let Ok((_stream, handle)) = rodio::OutputStream::try_default() {
+                if handle_sender.send(handle).is_ok() {
+                    let _ = stream_receiver.recv();
+                }
+            }
+        });
+        let handle = handle_receiver.recv().ok()?;
+        Some(Self {
+            handle,
+            _stream: stream,
+        })
+    }
+    fn play(&self, data: &SfxData, sfx: Sfx, volume: f32, pan: f32) -> Option<rodio::Sink> {
+        let decoder = rodio::Decoder::new(std::io::Cursor::new(data.sounds[&sfx].clone())).ok()?;
+        let angle = (pan + 1.0) * std::f32::consts::PI / 4.0;
+        let sink = rodio::Sink::try_new(&self.handle).ok()?;
+        sink.set_volume(volume);
+        sink.append(rodio::source::ChannelVolume::new(
+            decoder,
+            vec![angle.cos(), angle.sin()],
+        ));
+        Some(sink)
+    }
+}
+
+pub struct Mixer {
+    assets: Rc<Assets>,
+    #[cfg(not(target_arch = "wasm32"))]
+    native: Option<NativeAudio>,
+    voices: Vec<(Sfx, Voice)>,
+    time: f64,
+}
+
+impl Mixer {
+    pub fn new(assets: &Rc<Assets>) -> Self {
+        Self {
+            assets: assets.clone(),
+            #[cfg(not(target_arch = "wasm32"))]
+            native: NativeAudio::new(),
+            voices: Vec::new(),
+            time: 0.0,
+        }
+    }
+    pub fn update(&mut self, delta_time: f64) {
+        self.time += delta_time;
+        let time = self.time;
+        self.voices.retain(|(_, voice)| voice.playing(time));
+    }
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    pub fn play(&mut self, settings: &AudioSettings, sfx: Sfx, category: SoundCategory, pan: f32) {
+        let volume = settings.master_volume
+            * match category {
+                SoundCategory::Sfx => settings.sfx_volume,
+                SoundCategory::Ui => settings.ui_volume,
+            };
+        if settings.muted || volume <= 0.0 {
+            return;
+        }
+        let time = self.time;
+        if self
+            .voices
+            .iter()
+            .filter(|(other, voice)| *other == sfx && voice.playing(time))
+            .count()
+            >= sfx.max_voices()
+        {
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(native) = &self.native {
+                if let Some(sink) =
+                    native.play(&self.assets.sfx, sfx, volume as f32, clamp(pan, -1.0..=1.0))
+                {
+                    self.voices.push((sfx, Voice::Native(sink)));
+                }
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let mut effect = self.assets.sfx.sounds[&sfx].play();
+            effect.set_volume(volume);
+            self.voices
+                .push((sfx, Voice::Web(self.time + WEB_VOICE_TIME)));
+        }
+    }
+}
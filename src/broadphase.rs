@@ -0,0 +1,108 @@
+use super::*;
+
+pub struct Broadphase {
+    size: Vec2<usize>,
+    cells: Vec<Vec<usize>>,
+    max_radius: f32,
+}
+
+impl Broadphase {
+    pub fn new() -> Self {
+        Self {
+            size: vec2(0, 0),
+            cells: Vec::new(),
+            max_radius: 0.0,
+        }
+    }
+    fn cell(&self, pos: Vec2<f32>) -> Vec2<usize> {
+        vec2(
+            clamp(pos.x.floor() as i64, 0..=self.size.x as i64 - 1) as usize,
+            clamp(pos.y.floor() as i64, 0..=self.size.y as i64 - 1) as usize,
+        )
+    }
+    pub fn rebuild(&mut self, size: Vec2<usize>, projectiles: &[Projectile]) {
+        self.size = size;
+        self.cells.resize(size.x * size.y, Vec::new());
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+        self.max_radius = 0.0;
+        for (index, p) in projectiles.iter().enumerate() {
+            let cell = self.cell(p.pos);
+            self.cells[cell.x * size.y + cell.y].push(index);
+            self.max_radius = self.max_radius.max(p.radius);
+        }
+    }
+    pub fn query(&self, pos: Vec2<f32>, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let (min, max) = if self.cells.is_empty() {
+            // Empty range, nothing has been inserted yet
+            (vec2(1, 1), vec2(0, 0))
+        } else {
+            let reach = radius + self.max_radius;
+            (
+                self.cell(pos - vec2(reach, reach)),
+                self.cell(pos + vec2(reach, reach)),
+            )
+        };
+        (min.x..=max.x).flat_map(move |x| {
+            (min.y..=max.y).flat_map(move |y| self.cells[x * self.size.y + y].iter().copied())
+        })
+    }
+    pub fn pairs(&self, projectiles: &[Projectile]) -> Vec<(usize, usize)> {
+        let mut result = Vec::new();
+        for (i, p) in projectiles.iter().enumerate() {
+            for j in self.query(p.pos, p.radius) {
+                if j < i && (p.pos - projectiles[j].pos).len() < p.radius + projectiles[j].radius {
+                    result.push((i, j));
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let size = vec2(20, 10);
+        let projectiles: Vec<Projectile> = (0..500)
+            .map(|_| {
+                Projectile::new(
+                    vec2(rng.gen_range(-1.0..21.0), rng.gen_range(-1.0..11.0)),
+                    rng.gen_range(0.1..0.6),
+                    vec2(0.0, 0.0),
+                    None,
+                )
+            })
+            .collect();
+        let mut broadphase = Broadphase::new();
+        broadphase.rebuild(size, &projectiles);
+        let mut expected = Vec::new();
+        for (i, a) in projectiles.iter().enumerate() {
+            for (j, b) in projectiles[..i].iter().enumerate() {
+                if (a.pos - b.pos).len() < a.radius + b.radius {
+                    expected.push((i, j));
+                }
+            }
+        }
+        let mut pairs = broadphase.pairs(&projectiles);
+        pairs.sort();
+        assert_eq!(pairs, expected);
+        let pos = vec2(5.0, 5.0);
+        let found: Vec<usize> = broadphase.query(pos, 0.5).collect();
+        for (i, p) in projectiles.iter().enumerate() {
+            if (p.pos - pos).len() < p.radius + 0.5 {
+                assert!(found.contains(&i));
+            }
+        }
+    }
+
+    #[test]
+    fn query_before_rebuild_is_empty() {
+        assert_eq!(Broadphase::new().query(vec2(0.0, 0.0), 1.0).count(), 0);
+    }
+}
@@ -0,0 +1,414 @@
+use super::*;
+
+const KEY_NAMES: [(geng::Key, &str); 64] = [
+    (geng::Key::Num0, "Num0"),
+    (geng::Key::Num1, "Num1"),
+    (geng::Key::Num2, "Num2"),
+    (geng::Key::Num3, "Num3"),
+    (geng::Key::Num4, "Num4"),
+    (geng::Key::Num5, "Num5"),
+    (geng::Key::Num6, "Num6"),
+    (geng::Key::Num7, "Num7"),
+    (geng::Key::Num8, "Num8"),
+    (geng::Key::Num9, "Num9"),
+    (geng::Key::A, "A"),
+    (geng::Key::B, "B"),
+    (geng::Key::C, "C"),
+    (geng::Key::D, "D"),
+    (geng::Key::E, "E"),
+    (geng::Key::F, "F"),
+    (geng::Key::G, "G"),
+    (geng::Key::H, "H"),
+    (geng::Key::I, "I"),
+    (geng::Key::J, "J"),
+    (geng::Key::K, "K"),
+    (geng::Key::L, "L"),
+    (geng::Key::M, "M"),
+    (geng::Key::N, "N"),
+    (geng::Key::O, "O"),
+    (geng::Key::P, "P"),
+    (geng::Key::Q, "Q"),
+    (geng::Key::R, "R"),
+    (geng::Key::S, "S"),
+    (geng::Key::T, "T"),
+    (geng::Key::U, "U"),
+    (geng::Key::V, "V"),
+    (geng::Key::W, "W"),
+    (geng::Key::X, "X"),
+    (geng::Key::Y, "Y"),
+    (geng::Key::Z, "Z"),
+    (geng::Key::Escape, "Escape"),
+    (geng::Key::Space, "Space"),
+    (geng::Key::Enter, "Enter"),
+    (geng::Key::Backspace, "Backspace"),
+    (geng::Key::LShift, "LShift"),
+    (geng::Key::RShift, "RShift"),
+    (geng::Key::LCtrl, "LCtrl"),
+    (geng::Key::RCtrl, "RCtrl"),
+    (geng::Key::LAlt, "LAlt"),
+    (geng::Key::RAlt, "RAlt"),
+    (geng::Key::Left, "Left"),
+    (geng::Key::Right, "Right"),
+    (geng::Key::Up, "Up"),
+    (geng::Key::Down, "Down"),
+    (geng::Key::PageUp, "PageUp"),
+    (geng::Key::PageDown, "PageDown"),
+    (geng::Key::F1, "F1"),
+    (geng::Key::F2, "F2"),
+    (geng::Key::F3, "F3"),
+    (geng::Key::F4, "F4"),
+    (geng::Key::F5, "F5"),
+    (geng::Key::F6, "F6"),
+    (geng::Key::F7, "F7"),
+    (geng::Key::F8, "F8"),
+    (geng::Key::F9, "F9"),
+    (geng::Key::F10, "F10"),
+    (geng::Key::F11, "F11"),
+    (geng::Key::F12, "F12"),
+];
+
+const RESERVED_KEYS: [geng::Key; 8] = [
+    geng::Key::F1,
+    geng::Key::F2,
+    geng::Key::F3,
+    geng::Key::F4,
+    geng::Key::F5,
+    geng::Key::F6,
+    geng::Key::F8,
+    geng::Key::F9,
+];
+
+pub fn key_name(key: geng::Key) -> &'static str {
+    KEY_NAMES
+        .iter()
+        .find(|&&(other, _)| other == key)
+        .map_or("?", |&(_, name)| name)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KeyBinding(pub geng::Key);
+
+impl Serialize for KeyBinding {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(key_name(self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyBinding {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        KEY_NAMES
+            .iter()
+            .find(|&&(_, other)| other == name)
+            .map(|&(key, _)| KeyBinding(key))
+            .ok_or_else(|| serde::de::Error::custom(format!("Unknown key {:?}", name)))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Restart,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Restart,
+        Action::Pause,
+    ];
+    pub fn text(&self) -> &'static str {
+        match self {
+            Self::MoveUp => "controls.move_up",
+            Self::MoveDown => "controls.move_down",
+            Self::MoveLeft => "controls.move_left",
+            Self::MoveRight => "controls.move_right",
+            Self::Jump => "controls.jump",
+            Self::Restart => "controls.restart",
+            Self::Pause => "controls.pause",
+        }
+    }
+    fn default_keys(&self) -> Vec<KeyBinding> {
+        let keys: &[geng::Key] = match self {
+            Self::MoveUp => &[geng::Key::Up, geng::Key::W],
+            Self::MoveDown => &[geng::Key::Down, geng::Key::S],
+            Self::MoveLeft => &[geng::Key::Left, geng::Key::A],
+            Self::MoveRight => &[geng::Key::Right, geng::Key::D],
+            Self::Jump => &[geng::Key::Space],
+            Self::Restart => &[geng::Key::R],
+            Self::Pause => &[geng::Key::Escape],
+        };
+        keys.iter().map(|&key| KeyBinding(key)).collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Controls {
+    pub keys: HashMap<Action, Vec<KeyBinding>>,
+    pub dead_zone: f32,
+    #[serde(default)]
+    pub pointer: bool,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL
+                .iter()
+                .map(|&action| (action, action.default_keys()))
+                .collect(),
+            dead_zone: 0.2,
+            pointer: false,
+        }
+    }
+}
+
+impl Controls {
+    pub fn keys(&self, action: Action) -> &[KeyBinding] {
+        self.keys.get(&action).map_or(&[], |keys| keys)
+    }
+    pub fn keys_text(&self, action: Action) -> String {
+        self.keys(action)
+            .iter()
+            .map(|binding| key_name(binding.0))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+    pub fn action(&self, key: geng::Key) -> Option<Action> {
+        Action::ALL
+            .iter()
+            .copied()
+            .find(|&action| self.keys(action).contains(&KeyBinding(key)))
+    }
+    pub fn is_pressed(&self, window: &geng::Window, action: Action) -> bool {
+        self.keys(action)
+            .iter()
+            .any(|binding| window.is_key_pressed(binding.0))
+    }
+    pub fn move_direction(&self, window: &geng::Window) -> Vec2<f32> {
+        let mut direction = vec2(0.0, 0.0);
+        if self.is_pressed(window, Action::MoveUp) {
+            direction.y += 1.0;
+        }
+        if self.is_pressed(window, Action::MoveDown) {
+            direction.y -= 1.0;
+        }
+        if self.is_pressed(window, Action::MoveLeft) {
+            direction.x -= 1.0;
+        }
+        if self.is_pressed(window, Action::MoveRight) {
+            direction.x += 1.0;
+        }
+        direction
+    }
+    pub fn bind(&mut self, action: Action, key: geng::Key) -> bool {
+        if RESERVED_KEYS.contains(&key) {
+            return false;
+        }
+        let binding = KeyBinding(key);
+        let keys = self.keys.entry(action).or_insert_with(Vec::new);
+        let old = keys.first().copied();
+        if let Some(index) = keys.iter().position(|&bound| bound == binding) {
+            keys.swap(0, index);
+            return true;
+        }
+        match keys.first_mut() {
+            Some(primary) => *primary = binding,
+            None => keys.push(binding),
+        }
+        for (&other, keys) in &mut self.keys {
+            if other == action || !keys.contains(&binding) {
+                continue;
+            }
+            keys.retain(|&bound| bound != binding);
+            if keys.is_empty() {
+                keys.extend(old);
+            }
+        }
+        true
+    }
+}
+
+pub struct Pointer {
+    held: Option<Vec2<f32>>,
+    pressed: Option<Vec2<f32>>,
+    pub target: Option<Vec2<f32>>,
+    pub click: Option<Vec2<f32>>,
+}
+
+impl Pointer {
+    pub fn new() -> Self {
+        Self {
+            held: None,
+            pressed: None,
+            target: None,
+            click: None,
+        }
+    }
+    pub fn handle_event(&mut self, event: &geng::Event, enabled: bool) {
+        match event {
+            geng::Event::MouseDown {
+                position,
+                button: geng::MouseButton::Left,
+            } if enabled => {
+                self.held = Some(position.map(|x| x as f32));
+                self.pressed = self.held;
+            }
+            geng::Event::TouchStart { touches } if enabled => {
+                self.held = touches
+                    .first()
+                    .map(|touch| touch.position.map(|x| x as f32));
+                self.pressed = self.held;
+            }
+            geng::Event::MouseMove { position, .. } if self.held.is_some() => {
+                self.held = Some(position.map(|x| x as f32));
+            }
+            geng::Event::TouchMove { touches } if self.held.is_some() => {
+                if let Some(touch) = touches.first() {
+                    self.held = Some(touch.position.map(|x| x as f32));
+                }
+            }
+            geng::Event::MouseUp {
+                button: geng::MouseButton::Left,
+                ..
+            }
+            | geng::Event::TouchEnd => {
+                self.held = None;
+            }
+            _ => {}
+        }
+    }
+    pub fn update(&mut self, framebuffer: &ugli::Framebuffer, camera: &Camera) {
+        self.target = self
+            .held
+            .map(|pos| camera.screen_to_world(framebuffer, pos));
+        if let Some(pos) = self.pressed.take() {
+            self.click = Some(camera.screen_to_world(framebuffer, pos));
+        }
+    }
+    pub fn move_direction(&self, from: Vec2<f32>, dead_zone: f32) -> Vec2<f32> {
+        let delta = match self.target {
+            Some(target) => target - from,
+            None => return vec2(0.0, 0.0),
+        };
+        let len = delta.len();
+        if len <= dead_zone {
+            return vec2(0.0, 0.0);
+        }
+        delta / len * (len - dead_zone).min(1.0)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn gamepad_action(button: gilrs::Button) -> Option<Action> {
+    match button {
+        gilrs::Button::South => Some(Action::Jump),
+        gilrs::Button::North => Some(Action::Restart),
+        gilrs::Button::Start => Some(Action::Pause),
+        _ => None,
+    }
+}
+
+pub struct Gamepads {
+    #[cfg(not(target_arch = "wasm32"))]
+    gilrs: Option<gilrs::Gilrs>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            gilrs: gilrs::Gilrs::new().ok(),
+        }
+    }
+    pub fn poll(&mut self) -> Vec<Action> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(gilrs) = &mut self.gilrs {
+                let mut actions = Vec::new();
+                while let Some(event) = gilrs.next_event() {
+                    if let gilrs::EventType::ButtonPressed(button, _) = event.event {
+                        actions.extend(gamepad_action(button));
+                    }
+                }
+                return actions;
+            }
+        }
+        Vec::new()
+    }
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    pub fn stick(&self, dead_zone: f32) -> Vec2<f32> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(gilrs) = &self.gilrs {
+                for (_, gamepad) in gilrs.gamepads() {
+                    let stick = vec2(
+                        gamepad.value(gilrs::Axis::LeftStickX),
+                        gamepad.value(gilrs::Axis::LeftStickY),
+                    );
+                    let len = stick.len();
+                    if len > dead_zone {
+                        return stick / len
+                            * clamp((len - dead_zone) / (1.0 - dead_zone), 0.0..=1.0);
+                    }
+                }
+            }
+        }
+        vec2(0.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_a_key_moves_it_between_actions() {
+        let mut controls = Controls::default();
+        assert!(controls.bind(Action::MoveUp, geng::Key::S));
+        assert_eq!(
+            controls.keys(Action::MoveUp),
+            [KeyBinding(geng::Key::S), KeyBinding(geng::Key::W)]
+        );
+        assert_eq!(
+            controls.keys(Action::MoveDown),
+            [KeyBinding(geng::Key::Down)]
+        );
+        assert!(controls.bind(Action::Jump, geng::Key::R));
+        assert_eq!(controls.keys(Action::Jump), [KeyBinding(geng::Key::R)]);
+        assert_eq!(
+            controls.keys(Action::Restart),
+            [KeyBinding(geng::Key::Space)]
+        );
+        assert_eq!(controls.action(geng::Key::R), Some(Action::Jump));
+    }
+
+    #[test]
+    fn binding_an_alternate_makes_it_primary() {
+        let mut controls = Controls::default();
+        assert!(controls.bind(Action::MoveLeft, geng::Key::A));
+        assert_eq!(
+            controls.keys(Action::MoveLeft),
+            [KeyBinding(geng::Key::A), KeyBinding(geng::Key::Left)]
+        );
+        assert!(controls.bind(Action::MoveLeft, geng::Key::A));
+        assert_eq!(controls.keys(Action::MoveLeft).len(), 2);
+    }
+
+    #[test]
+    fn reserved_keys_are_rejected() {
+        let mut controls = Controls::default();
+        assert!(!controls.bind(Action::Jump, geng::Key::F1));
+        assert!(!controls.bind(Action::Jump, geng::Key::F9));
+        assert_eq!(controls.keys(Action::Jump), [KeyBinding(geng::Key::Space)]);
+    }
+}
@@ -0,0 +1,136 @@
+use super::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GrowthStage {
+    Seedling,
+    Sprout,
+    Ripe,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CropSpecies {
+    Quick,
+    Hearty,
+    Thorny,
+}
+
+impl Default for CropSpecies {
+    fn default() -> Self {
+        Self::Quick
+    }
+}
+
+impl CropSpecies {
+    pub const ALL: [CropSpecies; 3] =
+        [CropSpecies::Quick, CropSpecies::Hearty, CropSpecies::Thorny];
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Quick => "Quick",
+            Self::Hearty => "Hearty",
+            Self::Thorny => "Thorny",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|crop| crop.name() == name)
+    }
+    pub fn grow_time(&self) -> f32 {
+        match self {
+            Self::Quick => FERTILIZED_SOIL_TIME,
+            Self::Hearty => 6.0,
+            Self::Thorny => 4.0,
+        }
+    }
+    pub fn harvest(&self) -> usize {
+        match self {
+            Self::Quick => 1,
+            Self::Hearty => 3,
+            Self::Thorny => 2,
+        }
+    }
+    pub fn stage(&self, time: f32) -> GrowthStage {
+        let progress = 1.0 - time / self.grow_time();
+        if progress < 1.0 / 3.0 {
+            GrowthStage::Seedling
+        } else if progress < 2.0 / 3.0 {
+            GrowthStage::Sprout
+        } else {
+            GrowthStage::Ripe
+        }
+    }
+    pub fn hurts(&self, time: f32) -> bool {
+        *self == Self::Thorny && self.stage(time) != GrowthStage::Seedling
+    }
+    pub fn text(&self, stage: GrowthStage) -> &'static str {
+        match (self, stage) {
+            (Self::Quick, GrowthStage::Seedling) => "tile.quick_crop_seedling",
+            (Self::Quick, GrowthStage::Sprout) => "tile.quick_crop_sprout",
+            (Self::Quick, GrowthStage::Ripe) => "tile.quick_crop_ripe",
+            (Self::Hearty, GrowthStage::Seedling) => "tile.hearty_crop_seedling",
+            (Self::Hearty, GrowthStage::Sprout) => "tile.hearty_crop_sprout",
+            (Self::Hearty, GrowthStage::Ripe) => "tile.hearty_crop_ripe",
+            (Self::Thorny, GrowthStage::Seedling) => "tile.thorny_crop_seedling",
+            (Self::Thorny, GrowthStage::Sprout) => "tile.thorny_crop_sprout",
+            (Self::Thorny, GrowthStage::Ripe) => "tile.thorny_crop_ripe",
+        }
+    }
+    pub fn draw(
+        &self,
+        framebuffer: &mut ugli::Framebuffer,
+        camera: &Camera,
+        primitive: &Primitive,
+        pos: Vec2<f32>,
+        time: f32,
+        mutation: Option<Mutation>,
+    ) {
+        let stage = self.stage(time);
+        let stems: &[f32] = match self {
+            Self::Hearty => &[-0.12, 0.12],
+            Self::Quick | Self::Thorny => &[0.0],
+        };
+        let height = match stage {
+            GrowthStage::Seedling => 0.1,
+            GrowthStage::Sprout => 0.25,
+            GrowthStage::Ripe => 0.35,
+        };
+        for &dx in stems {
+            let base = pos + vec2(0.5 + dx, 0.45);
+            let top = base + vec2(0.0, height);
+            primitive.line(framebuffer, camera, base, top, 0.06, Color::BLACK);
+            if stage != GrowthStage::Seedling {
+                for &side in &[-1.0, 1.0] {
+                    primitive.line(
+                        framebuffer,
+                        camera,
+                        base + vec2(0.0, height * 0.5),
+                        base + vec2(side * 0.1, height * 0.7),
+                        0.05,
+                        Color::BLACK,
+                    );
+                }
+            }
+            if *self == Self::Thorny && stage != GrowthStage::Seedling {
+                for i in 0..3 {
+                    let at = base + vec2(0.0, height * (i as f32 + 0.5) / 3.0);
+                    primitive.line(
+                        framebuffer,
+                        camera,
+                        at - vec2(0.07, 0.03),
+                        at + vec2(0.07, 0.03),
+                        0.03,
+                        Color::BLACK,
+                    );
+                }
+            }
+            if stage == GrowthStage::Ripe {
+                primitive.circle(framebuffer, camera, top, 0.1, Color::BLACK);
+                primitive.circle(
+                    framebuffer,
+                    camera,
+                    top,
+                    0.06,
+                    mutation.map_or(Color::WHITE, |m| m.color()),
+                );
+            }
+        }
+    }
+}
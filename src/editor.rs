@@ -0,0 +1,315 @@
+use super::*;
+
+const BRUSHES: [Tile; 18] = [
+    Tile::Nothing,
+    Tile::BrokenShell,
+    Tile::CrushedShell,
+    Tile::FertilizedSoil {
+        time: FERTILIZED_SOIL_TIME,
+        mutation: None,
+        crop: CropSpecies::Quick,
+    },
+    Tile::FertilizedSoil {
+        time: FERTILIZED_SOIL_TIME,
+        mutation: None,
+        crop: CropSpecies::Hearty,
+    },
+    Tile::FertilizedSoil {
+        time: FERTILIZED_SOIL_TIME,
+        mutation: None,
+        crop: CropSpecies::Thorny,
+    },
+    Tile::Food { mutation: None },
+    Tile::Poop { mutation: None },
+    Tile::AngryWeed {
+        time: ANGRY_WEED_SHOOT_TIME,
+        mutation: None,
+        species: WeedSpecies::Angry,
+    },
+    Tile::AngryWeed {
+        time: ANGRY_WEED_SHOOT_TIME,
+        mutation: None,
+        species: WeedSpecies::Spreader,
+    },
+    Tile::AngryWeed {
+        time: ANGRY_WEED_SHOOT_TIME,
+        mutation: None,
+        species: WeedSpecies::Sprinkler,
+    },
+    Tile::AngryWeed {
+        time: ANGRY_WEED_SHOOT_TIME,
+        mutation: None,
+        species: WeedSpecies::Burst { shots: 0 },
+    },
+    Tile::AngryWeed {
+        time: ANGRY_WEED_SHOOT_TIME,
+        mutation: None,
+        species: WeedSpecies::Sniper { aim: None },
+    },
+    Tile::MutatedRoot,
+    Tile::Bleach { mutation: None },
+    Tile::Trophey {
+        mutation: Mutation { channels: 1 },
+    },
+    Tile::Wall,
+    Tile::Rock,
+];
+
+fn mutations() -> Vec<Option<Mutation>> {
+    std::iter::once(None)
+        .chain(Mutation::all().into_iter().map(Some))
+        .collect()
+}
+
+enum Status {
+    Brush,
+    Saved,
+    SaveFailed(String),
+    Loaded,
+    LoadFailed(String),
+}
+
+pub struct Editor {
+    geng: Rc<Geng>,
+    pub level: Level,
+    path: std::path::PathBuf,
+    camera: Camera,
+    brush: usize,
+    mutation: usize,
+    cursor: Vec2<f32>,
+    status: Status,
+}
+
+impl Editor {
+    pub fn new(geng: &Rc<Geng>, level: Level, path: std::path::PathBuf) -> Self {
+        Self {
+            geng: geng.clone(),
+            level,
+            path,
+            camera: Camera::new(0.1),
+            brush: 0,
+            mutation: 0,
+            cursor: vec2(0.0, 0.0),
+            status: Status::Brush,
+        }
+    }
+    fn brush(&self) -> Tile {
+        let mutation = mutations()[self.mutation];
+        match BRUSHES[self.brush] {
+            Tile::FertilizedSoil { crop, .. } => Tile::FertilizedSoil {
+                time: crop.grow_time(),
+                mutation,
+                crop,
+            },
+            Tile::Food { .. } => Tile::Food { mutation },
+            Tile::Poop { .. } => Tile::Poop { mutation },
+            Tile::Bleach { .. } => Tile::Bleach { mutation },
+            Tile::AngryWeed { species, .. } => Tile::AngryWeed {
+                time: species.shoot_time(),
+                mutation,
+                species,
+            },
+            Tile::Trophey { .. } => Tile::Trophey {
+                mutation: mutation.unwrap_or(Mutation::all()[0]),
+            },
+            tile => tile,
+        }
+    }
+    fn status_text(&self, locale: &Locale) -> String {
+        let path = self.path.display();
+        match &self.status {
+            Status::Brush => {
+                let mut text = locale.get(self.brush().text()).to_owned();
+                if let Some(mutation) = mutations()[self.mutation] {
+                    text += &format!(" ({})", locale.get(&mutation.text()));
+                }
+                let size = self.level.map.size();
+                format!("{} {}x{}", text, size.x, size.y)
+            }
+            Status::Saved => locale.format("editor.saved", path),
+            Status::SaveFailed(e) => locale.format("editor.save_failed", e),
+            Status::Loaded => locale.format("editor.loaded", path),
+            Status::LoadFailed(e) => locale.format("editor.load_failed", e),
+        }
+    }
+    fn cursor_cell(&self) -> Option<Vec2<usize>> {
+        let size = self.level.map.size();
+        if self.cursor.x < 0.0
+            || self.cursor.y < 0.0
+            || self.cursor.x >= size.x as f32
+            || self.cursor.y >= size.y as f32
+        {
+            return None;
+        }
+        Some(self.cursor.map(|x| x as usize))
+    }
+    fn resize(&mut self, dx: isize, dy: isize) {
+        let size = self.level.map.size();
+        let size = vec2(
+            max(size.x as isize + dx, 1) as usize,
+            max(size.y as isize + dy, 1) as usize,
+        );
+        self.level.map.resize(size);
+        self.level.spawn = vec2(
+            clamp(self.level.spawn.x, 0.0..=size.x as f32 - 0.5),
+            clamp(self.level.spawn.y, 0.0..=size.y as f32 - 0.5),
+        );
+        if self.level.map.is_solid(self.level.spawn.map(|x| x as i32)) {
+            self.move_spawn();
+        }
+        self.status = Status::Brush;
+    }
+    fn move_spawn(&mut self) {
+        let size = self.level.map.size();
+        let spawn = self.level.spawn;
+        let free = (0..size.x)
+            .flat_map(|x| (0..size.y).map(move |y| vec2(x, y)))
+            .filter(|pos| !self.level.map.tiles[pos.x][pos.y].is_solid())
+            .map(|pos| pos.map(|x| x as f32 + 0.5))
+            .min_by(|a, b| (*a - spawn).len().partial_cmp(&(*b - spawn).len()).unwrap());
+        match free {
+            Some(pos) => self.level.spawn = pos,
+            None => {
+                let pos = spawn.map(|x| x as usize);
+                self.level.map.tiles[pos.x][pos.y] = Tile::Nothing;
+            }
+        }
+    }
+    fn save(&mut self) {
+        let result = self
+            .level
+            .to_source()
+            .and_then(|source| std::fs::write(&self.path, source).map_err(|e| e.to_string()));
+        self.status = match result {
+            Ok(()) => Status::Saved,
+            Err(e) => Status::SaveFailed(e),
+        };
+    }
+    fn load(&mut self) {
+        let result = std::fs::read_to_string(&self.path)
+            .map_err(|e| e.to_string())
+            .and_then(|source| Level::parse(&source).map_err(|e| e.to_string()));
+        self.status = match result {
+            Ok(level) => {
+                self.level = level;
+                Status::Loaded
+            }
+            Err(e) => Status::LoadFailed(e),
+        };
+    }
+    pub fn update(&mut self, delta_time: f32) {
+        let size = self.level.map.size();
+        self.camera.center = size.map(|x| x as f32) / 2.0;
+        self.camera.target_fov = max(size.x, size.y) as f32 + 2.0;
+        self.camera.update(delta_time);
+        let tile = if self
+            .geng
+            .window()
+            .is_button_pressed(geng::MouseButton::Left)
+        {
+            self.brush()
+        } else if self
+            .geng
+            .window()
+            .is_button_pressed(geng::MouseButton::Right)
+        {
+            Tile::Nothing
+        } else {
+            return;
+        };
+        if let Some(pos) = self.cursor_cell() {
+            if tile.is_solid() && pos == self.level.spawn.map(|x| x as usize) {
+                return;
+            }
+            self.level.map.tiles[pos.x][pos.y] = tile;
+        }
+    }
+    pub fn draw(
+        &mut self,
+        framebuffer: &mut ugli::Framebuffer,
+        primitive: &Primitive,
+        locale: &Locale,
+    ) {
+        ugli::clear(framebuffer, Some(Color::WHITE), None);
+        self.cursor = self.camera.screen_to_world(
+            framebuffer,
+            self.geng.window().mouse_pos().map(|x| x as f32),
+        );
+        self.level
+            .map
+            .draw(framebuffer, &self.camera, primitive, Stage::Born);
+        if let Some(pos) = self.cursor_cell() {
+            primitive.quad(
+                framebuffer,
+                &self.camera,
+                AABB::pos_size(pos.map(|x| x as f32), vec2(1.0, 1.0)),
+                Color::rgba(0.0, 0.0, 0.0, 0.2),
+            );
+        }
+        primitive.circle(
+            framebuffer,
+            &self.camera,
+            self.level.spawn,
+            0.3,
+            Color::BLACK,
+        );
+        primitive.circle(
+            framebuffer,
+            &self.camera,
+            self.level.spawn,
+            0.2,
+            Color::rgb(1.0, 1.0, 0.0),
+        );
+        let status_pos = self
+            .camera
+            .screen_to_world(framebuffer, vec2(0.0, framebuffer.size().y as f32))
+            + vec2(self.camera.fov / 20.0, -self.camera.fov / 10.0);
+        primitive.text_bubble(
+            framebuffer,
+            &self.camera,
+            self.status_text(locale),
+            status_pos,
+            self.camera.fov / 30.0,
+        );
+        primitive.flush(framebuffer, &self.camera);
+    }
+    pub fn handle_event(&mut self, event: geng::Event) {
+        let ctrl = self.geng.window().is_key_pressed(geng::Key::LCtrl)
+            || self.geng.window().is_key_pressed(geng::Key::RCtrl);
+        if let geng::Event::KeyDown { key } = event {
+            match key {
+                geng::Key::Q => {
+                    self.brush = (self.brush + BRUSHES.len() - 1) % BRUSHES.len();
+                    self.status = Status::Brush;
+                }
+                geng::Key::E => {
+                    self.brush = (self.brush + 1) % BRUSHES.len();
+                    self.status = Status::Brush;
+                }
+                geng::Key::Z => {
+                    let count = mutations().len();
+                    self.mutation = (self.mutation + count - 1) % count;
+                    self.status = Status::Brush;
+                }
+                geng::Key::C => {
+                    self.mutation = (self.mutation + 1) % mutations().len();
+                    self.status = Status::Brush;
+                }
+                geng::Key::P => {
+                    if let Some(pos) = self.cursor_cell() {
+                        if !self.level.map.tiles[pos.x][pos.y].is_solid() {
+                            self.level.spawn = self.cursor;
+                        }
+                    }
+                }
+                geng::Key::Left => self.resize(-1, 0),
+                geng::Key::Right => self.resize(1, 0),
+                geng::Key::Down => self.resize(0, -1),
+                geng::Key::Up => self.resize(0, 1),
+                geng::Key::S if ctrl => self.save(),
+                geng::Key::L if ctrl => self.load(),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use super::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Sfx {
+    Spit,
+    Eat,
+    Smoke,
+    Crack,
+    Birth,
+    Poop,
+    Hit,
+    Death,
+    Jump,
+    ProjectileHit,
+    Weed,
+    WeedDeath,
+    Trophey,
+    Win,
+    Mutate,
+}
+
+impl Sfx {
+    pub const ALL: [Sfx; 15] = [
+        Sfx::Spit,
+        Sfx::Eat,
+        Sfx::Smoke,
+        Sfx::Crack,
+        Sfx::Birth,
+        Sfx::Poop,
+        Sfx::Hit,
+        Sfx::Death,
+        Sfx::Jump,
+        Sfx::ProjectileHit,
+        Sfx::Weed,
+        Sfx::WeedDeath,
+        Sfx::Trophey,
+        Sfx::Win,
+        Sfx::Mutate,
+    ];
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::Spit => "spit.wav",
+            Self::Eat => "eat.wav",
+            Self::Smoke => "smoke.wav",
+            Self::Crack => "crack.wav",
+            Self::Birth => "birth.wav",
+            Self::Poop => "poop.wav",
+            Self::Hit => "hit.wav",
+            Self::Death => "death.wav",
+            Self::Jump => "jump.wav",
+            Self::ProjectileHit => "projectile_hit.wav",
+            Self::Weed => "weed.wav",
+            Self::WeedDeath => "weed_death.wav",
+            Self::Trophey => "trophey.wav",
+            Self::Win => "win.wav",
+            Self::Mutate => "mutate.wav",
+        }
+    }
+    pub fn max_voices(&self) -> usize {
+        match self {
+            Self::Smoke | Self::ProjectileHit | Self::Spit => 2,
+            Self::Birth | Self::Win | Self::Death => 1,
+            _ => 3,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GameEvent {
+    StageChanged {
+        stage: Stage,
+    },
+    ShellCracked {
+        pos: Vec2<f32>,
+    },
+    ShellDropped {
+        pos: Vec2<f32>,
+    },
+    Hatched {
+        pos: Vec2<f32>,
+    },
+    Jumped {
+        pos: Vec2<f32>,
+    },
+    ShellCrushed {
+        pos: Vec2<f32>,
+    },
+    SoilFertilized {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    SoilCleared {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    FoodGrown {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    WeedSprouted {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    FoodEaten {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    Pooped {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    ProjectileFired {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    ProjectileDestroyed {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    WeedKilled {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    RootDestroyed {
+        pos: Vec2<f32>,
+    },
+    RootSpread {
+        pos: Vec2<f32>,
+    },
+    FoodPollinated {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    Mutated {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    TropheyCollected {
+        pos: Vec2<f32>,
+        mutation: Mutation,
+    },
+    Bleached {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    PlayerHit {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    PlayerDied {
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+    },
+    Won,
+}
+
+impl GameEvent {
+    pub fn pos(&self) -> Option<Vec2<f32>> {
+        match *self {
+            Self::StageChanged { .. } | Self::Won => None,
+            Self::ShellCracked { pos }
+            | Self::ShellDropped { pos }
+            | Self::Hatched { pos }
+            | Self::Jumped { pos }
+            | Self::ShellCrushed { pos }
+            | Self::RootDestroyed { pos }
+            | Self::RootSpread { pos }
+            | Self::SoilFertilized { pos, .. }
+            | Self::SoilCleared { pos, .. }
+            | Self::FoodGrown { pos, .. }
+            | Self::FoodPollinated { pos, .. }
+            | Self::WeedSprouted { pos, .. }
+            | Self::FoodEaten { pos, .. }
+            | Self::Pooped { pos, .. }
+            | Self::ProjectileFired { pos, .. }
+            | Self::ProjectileDestroyed { pos, .. }
+            | Self::WeedKilled { pos, .. }
+            | Self::Mutated { pos, .. }
+            | Self::Bleached { pos, .. }
+            | Self::TropheyCollected { pos, .. }
+            | Self::PlayerHit { pos, .. }
+            | Self::PlayerDied { pos, .. } => Some(pos),
+        }
+    }
+    pub fn sounds(&self) -> &'static [Sfx] {
+        match self {
+            Self::StageChanged { .. } | Self::ShellDropped { .. } => &[],
+            Self::ShellCracked { .. } => &[Sfx::Crack, Sfx::Smoke],
+            Self::Hatched { .. } => &[Sfx::Birth],
+            Self::Jumped { .. } => &[Sfx::Jump],
+            Self::ShellCrushed { .. } => &[Sfx::Crack, Sfx::Smoke],
+            Self::SoilFertilized { .. }
+            | Self::SoilCleared { .. }
+            | Self::FoodGrown { .. }
+            | Self::RootDestroyed { .. }
+            | Self::RootSpread { .. } => &[Sfx::Smoke],
+            Self::FoodPollinated { .. } => &[Sfx::Mutate, Sfx::Smoke],
+            Self::WeedSprouted { .. } => &[Sfx::Weed, Sfx::Smoke],
+            Self::FoodEaten { .. } => &[Sfx::Eat],
+            Self::Pooped { .. } => &[Sfx::Poop, Sfx::Smoke],
+            Self::ProjectileFired { .. } => &[Sfx::Spit],
+            Self::ProjectileDestroyed { .. } => &[Sfx::ProjectileHit, Sfx::Smoke],
+            Self::WeedKilled { .. } => &[Sfx::WeedDeath],
+            Self::Mutated { .. } => &[Sfx::Mutate],
+            Self::Bleached { .. } => &[Sfx::Mutate, Sfx::Smoke],
+            Self::TropheyCollected { .. } => &[Sfx::Trophey],
+            Self::PlayerHit { .. } => &[Sfx::Hit],
+            Self::PlayerDied { .. } => &[Sfx::Death],
+            Self::Won => &[Sfx::Win],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub shells_cracked: usize,
+    pub jumps: usize,
+    pub food_eaten: usize,
+    pub poops: usize,
+    pub weeds_sprouted: usize,
+    pub weeds_killed: usize,
+    pub projectiles_fired: usize,
+    pub hits_taken: usize,
+    pub mutations: usize,
+    pub tropheys: usize,
+    pub deaths: usize,
+    #[serde(default)]
+    pub time: f32,
+}
+
+impl Stats {
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::ShellCracked { .. } => self.shells_cracked += 1,
+            GameEvent::Jumped { .. } => self.jumps += 1,
+            GameEvent::FoodEaten { .. } => self.food_eaten += 1,
+            GameEvent::Pooped { .. } => self.poops += 1,
+            GameEvent::WeedSprouted { .. } => self.weeds_sprouted += 1,
+            GameEvent::WeedKilled { .. } => self.weeds_killed += 1,
+            GameEvent::ProjectileFired { .. } => self.projectiles_fired += 1,
+            GameEvent::PlayerHit { .. } => self.hits_taken += 1,
+            GameEvent::Mutated { .. } => self.mutations += 1,
+            GameEvent::TropheyCollected { .. } => self.tropheys += 1,
+            GameEvent::PlayerDied { .. } => self.deaths += 1,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cracking_the_shell_is_counted() {
+        let mut simulation = Simulation::new(Level::default(), Rc::new(Script::default()), 1);
+        for frame in 0..1800 {
+            let direction = if (frame / 300) % 2 == 0 { 1.0 } else { -1.0 };
+            let input = Input {
+                move_direction: vec2(direction, 0.0),
+                jump: false,
+            };
+            simulation.update(1.0 / 60.0, input);
+        }
+        let count = |f: fn(&GameEvent) -> bool| simulation.events.iter().filter(|e| f(e)).count();
+        let cracked = count(|e| matches!(e, GameEvent::ShellCracked { .. }));
+        assert!(cracked >= 3);
+        assert_eq!(simulation.stats.shells_cracked, cracked);
+        assert_eq!(count(|e| matches!(e, GameEvent::Hatched { .. })), 1);
+        assert_eq!(
+            count(|e| matches!(e, GameEvent::ShellDropped { .. })),
+            cracked
+        );
+        assert_eq!(simulation.stats.jumps, 0);
+        assert_eq!(simulation.stage, Stage::Born);
+        assert!((simulation.stats.time - 30.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn stats_follow_events() {
+        let mut stats = Stats::default();
+        let pos = vec2(0.0, 0.0);
+        for event in &[
+            GameEvent::Jumped { pos },
+            GameEvent::Jumped { pos },
+            GameEvent::FoodEaten {
+                pos,
+                mutation: None,
+            },
+            GameEvent::PlayerHit {
+                pos,
+                mutation: None,
+            },
+            GameEvent::Won,
+        ] {
+            stats.handle_event(event);
+        }
+        assert_eq!(stats.jumps, 2);
+        assert_eq!(stats.food_eaten, 1);
+        assert_eq!(stats.hits_taken, 1);
+        assert_eq!(stats.deaths, 0);
+    }
+}
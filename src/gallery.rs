@@ -0,0 +1,67 @@
+use super::*;
+
+pub struct Gallery {
+    menu: Menu,
+}
+
+impl Gallery {
+    pub fn new() -> Self {
+        Self { menu: Menu::new() }
+    }
+    fn items(locale: &Locale, profile: &Profile) -> Vec<String> {
+        let status = |unlock: Option<&Option<u64>>, progress: Option<String>| match unlock {
+            Some(Some(time)) => format_date(*time),
+            Some(None) => locale.get("gallery.unlocked").to_owned(),
+            None => progress.unwrap_or_else(|| locale.get("gallery.locked").to_owned()),
+        };
+        let mut items: Vec<String> = Mutation::all()
+            .into_iter()
+            .map(|mutation| {
+                format!(
+                    "{}: {}",
+                    locale.format("gallery.trophey", locale.get(&mutation.text())),
+                    status(profile.tropheys.get(&mutation), None),
+                )
+            })
+            .collect();
+        for &achievement in &Achievement::ALL {
+            items.push(format!(
+                "{}: {}",
+                locale.get(achievement.text()),
+                status(
+                    profile.achievements.get(&achievement),
+                    profile.progress(achievement),
+                ),
+            ));
+        }
+        items.push(locale.get("options.back").to_owned());
+        items
+    }
+    pub fn handle_event(&mut self, event: &geng::Event, controls: &Controls) -> bool {
+        let count = Mutation::all().len() + Achievement::ALL.len() + 1;
+        match self.menu.handle_event(event, count, controls) {
+            Some(MenuAction::Activate(index)) => index + 1 == count,
+            Some(MenuAction::Back) => true,
+            _ => false,
+        }
+    }
+    pub fn draw(
+        &mut self,
+        framebuffer: &mut ugli::Framebuffer,
+        camera: &Camera,
+        primitive: &Primitive,
+        mouse_pos: Vec2<f32>,
+        locale: &Locale,
+        profile: &Profile,
+    ) {
+        let items = Self::items(locale, profile);
+        self.menu.draw(
+            framebuffer,
+            camera,
+            primitive,
+            mouse_pos,
+            locale.get("gallery.title"),
+            &items,
+        );
+    }
+}
@@ -0,0 +1,427 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+#[derive(Debug, Copy, Clone)]
+struct Token<'a> {
+    line: usize,
+    column: usize,
+    text: &'a str,
+}
+
+impl<'a> Token<'a> {
+    fn error(&self, message: impl Into<String>) -> LevelError {
+        LevelError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+    fn parse<T: std::str::FromStr>(&self, what: &str) -> Result<T, LevelError> {
+        self.text
+            .parse()
+            .map_err(|_| self.error(format!("Expected {}, got {:?}", what, self.text)))
+    }
+    fn parse_name<T: for<'de> Deserialize<'de>>(&self, what: &str) -> Result<T, LevelError> {
+        serde_json::from_value(serde_json::Value::String(self.text.to_owned()))
+            .map_err(|_| self.error(format!("Unknown {} {:?}", what, self.text)))
+    }
+    fn split(&self, separator: char) -> Option<(Token<'a>, Token<'a>)> {
+        let index = self.text.find(separator)?;
+        let key = &self.text[..index];
+        Some((
+            Token { text: key, ..*self },
+            Token {
+                line: self.line,
+                column: self.column + key.chars().count() + 1,
+                text: &self.text[index + separator.len_utf8()..],
+            },
+        ))
+    }
+}
+
+fn tokenize(line: usize, text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (column, (index, c)) in text.char_indices().enumerate() {
+        if c.is_whitespace() {
+            if let Some((start_column, start_index)) = start.take() {
+                tokens.push(Token {
+                    line,
+                    column: start_column + 1,
+                    text: &text[start_index..index],
+                });
+            }
+        } else if start.is_none() {
+            start = Some((column, index));
+        }
+    }
+    if let Some((start_column, start_index)) = start {
+        tokens.push(Token {
+            line,
+            column: start_column + 1,
+            text: &text[start_index..],
+        });
+    }
+    tokens
+}
+
+fn tile_source(tile: &Tile) -> String {
+    let mut result = format!("{:?}", tile.kind());
+    if let Tile::FertilizedSoil { time, .. } | Tile::AngryWeed { time, .. } = tile {
+        result += &format!(" time={}", time);
+    }
+    if let Some(mutation) = tile.mutation() {
+        result += &format!(" mutation={}", mutation.name());
+    }
+    if let Tile::AngryWeed { species, .. } = tile {
+        if *species != WeedSpecies::Angry {
+            result += &format!(" species={}", species.name());
+        }
+    }
+    if let Tile::FertilizedSoil { crop, .. } = tile {
+        if *crop != CropSpecies::Quick {
+            result += &format!(" crop={}", crop.name());
+        }
+    }
+    result
+}
+
+fn parse_tile(name: Token, fields: &[Token]) -> Result<Tile, LevelError> {
+    let mut time = None;
+    let mut mutation = None;
+    let mut species = None;
+    let mut crop = None;
+    for field in fields {
+        let (key, value) = field
+            .split('=')
+            .ok_or_else(|| field.error("Expected key=value"))?;
+        match key.text {
+            "time" => time = Some((key, value.parse::<f32>("a number")?)),
+            "mutation" => mutation = Some((key, value.parse_name::<Mutation>("mutation")?)),
+            "species" => {
+                species = Some((
+                    key,
+                    WeedSpecies::from_name(value.text)
+                        .ok_or_else(|| value.error(format!("Unknown species {:?}", value.text)))?,
+                ))
+            }
+            "crop" => {
+                crop = Some((
+                    key,
+                    CropSpecies::from_name(value.text)
+                        .ok_or_else(|| value.error(format!("Unknown crop {:?}", value.text)))?,
+                ))
+            }
+            _ => return Err(key.error(format!("Unknown field {:?}", key.text))),
+        }
+    }
+    let tile = match name.text {
+        "Nothing" => Tile::Nothing,
+        "BrokenShell" => Tile::BrokenShell,
+        "CrushedShell" => Tile::CrushedShell,
+        "FertilizedSoil" => {
+            let crop = crop.take().map_or(CropSpecies::Quick, |(_, crop)| crop);
+            Tile::FertilizedSoil {
+                time: time.take().map_or(crop.grow_time(), |(_, time)| time),
+                mutation: mutation.take().map(|(_, mutation)| mutation),
+                crop,
+            }
+        }
+        "Food" => Tile::Food {
+            mutation: mutation.take().map(|(_, mutation)| mutation),
+        },
+        "Poop" => Tile::Poop {
+            mutation: mutation.take().map(|(_, mutation)| mutation),
+        },
+        "AngryWeed" => {
+            let species = species
+                .take()
+                .map_or(WeedSpecies::Angry, |(_, species)| species);
+            Tile::AngryWeed {
+                time: time.take().map_or(species.shoot_time(), |(_, time)| time),
+                mutation: mutation.take().map(|(_, mutation)| mutation),
+                species,
+            }
+        }
+        "MutatedRoot" => Tile::MutatedRoot,
+        "Bleach" => Tile::Bleach {
+            mutation: mutation.take().map(|(_, mutation)| mutation),
+        },
+        "Wall" => Tile::Wall,
+        "Rock" => Tile::Rock,
+        "Trophey" => Tile::Trophey {
+            mutation: mutation
+                .take()
+                .map(|(_, mutation)| mutation)
+                .ok_or_else(|| name.error("Trophey requires a mutation"))?,
+        },
+        _ => return Err(name.error(format!("Unknown tile {:?}", name.text))),
+    };
+    if let Some((key, _)) = time {
+        return Err(key.error(format!("{} has no time", name.text)));
+    }
+    if let Some((key, _)) = mutation {
+        return Err(key.error(format!("{} has no mutation", name.text)));
+    }
+    if let Some((key, _)) = species {
+        return Err(key.error(format!("{} has no species", name.text)));
+    }
+    if let Some((key, _)) = crop {
+        return Err(key.error(format!("{} has no crop", name.text)));
+    }
+    Ok(tile)
+}
+
+#[derive(Clone)]
+pub struct Level {
+    pub map: Map,
+    pub spawn: Vec2<f32>,
+    pub stage: Stage,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        let map = Map::new();
+        let spawn = map.size().map(|x| x as f32) / 2.0;
+        Self {
+            map,
+            spawn,
+            stage: Stage::Start,
+        }
+    }
+}
+
+impl Level {
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read level {}: {}", path.display(), e))?;
+        Self::parse(&source).map_err(|e| format!("{}:{}", path.display(), e))
+    }
+    pub fn to_source(&self) -> Result<String, String> {
+        let size = self.map.size();
+        let mut legend = vec![(Tile::Nothing, '.'), (Tile::Wall, '#')];
+        let mut symbols = ('!'..='~').filter(|c| *c != '.' && *c != '#');
+        let symbol_count = symbols.clone().count();
+        let mut grid = String::new();
+        for y in (0..size.y).rev() {
+            for x in 0..size.x {
+                let tile = self.map.tiles[x][y];
+                let symbol = match legend.iter().find(|(other, _)| *other == tile) {
+                    Some(&(_, symbol)) => symbol,
+                    None => {
+                        let symbol = symbols.next().ok_or_else(|| {
+                            format!("Level has more than {} different tiles", symbol_count + 2)
+                        })?;
+                        legend.push((tile, symbol));
+                        symbol
+                    }
+                };
+                grid.push(symbol);
+            }
+            grid.push('\n');
+        }
+        let mut result = String::new();
+        result += &format!("size {} {}\n", size.x, size.y);
+        result += &format!("peace {}\n", self.map.peace());
+        result += &format!("stage {:?}\n", self.stage);
+        result += &format!("spawn {} {}\n", self.spawn.x, self.spawn.y);
+        result += "\n";
+        for (tile, symbol) in &legend[2..] {
+            result += &format!("tile {} {}\n", symbol, tile_source(tile));
+        }
+        result += "\ngrid\n";
+        result += &grid;
+        Ok(result)
+    }
+    pub fn parse(source: &str) -> Result<Self, LevelError> {
+        let mut size = None;
+        let mut peace = 3;
+        let mut stage = Stage::Start;
+        let mut spawn = None;
+        let mut legend = HashMap::new();
+        legend.insert('.', Tile::Nothing);
+        legend.insert('#', Tile::Wall);
+        let mut grid = None;
+        let mut lines = source.lines().enumerate();
+        while let Some((index, line)) = lines.next() {
+            let tokens = tokenize(index + 1, line);
+            let (directive, args) = match tokens.split_first() {
+                Some((directive, _)) if directive.text.starts_with('#') => continue,
+                Some(split) => split,
+                None => continue,
+            };
+            let expect_args = |count: usize| {
+                if args.len() == count {
+                    Ok(())
+                } else {
+                    Err(directive.error(format!(
+                        "{} expects {} argument(s), got {}",
+                        directive.text,
+                        count,
+                        args.len()
+                    )))
+                }
+            };
+            match directive.text {
+                "size" => {
+                    expect_args(2)?;
+                    let x: usize = args[0].parse("a width")?;
+                    let y: usize = args[1].parse("a height")?;
+                    if x == 0 || y == 0 {
+                        return Err(args[0].error("Map size must be positive"));
+                    }
+                    size = Some(vec2(x, y));
+                }
+                "peace" => {
+                    expect_args(1)?;
+                    peace = args[0].parse("a number")?;
+                }
+                "stage" => {
+                    expect_args(1)?;
+                    stage = args[0].parse_name("stage")?;
+                }
+                "spawn" => {
+                    expect_args(2)?;
+                    spawn = Some((
+                        *directive,
+                        vec2(args[0].parse("a number")?, args[1].parse("a number")?),
+                    ));
+                }
+                "tile" => {
+                    if args.len() < 2 {
+                        return Err(directive.error("tile expects a symbol and a tile name"));
+                    }
+                    let mut symbol = args[0].text.chars();
+                    let symbol = match (symbol.next(), symbol.next()) {
+                        (Some(c), None) => c,
+                        _ => return Err(args[0].error("Tile symbol must be a single character")),
+                    };
+                    legend.insert(symbol, parse_tile(args[1], &args[2..])?);
+                }
+                "grid" => {
+                    expect_args(0)?;
+                    let rows: Vec<(usize, &str)> = lines
+                        .by_ref()
+                        .map(|(index, line)| (index + 1, line.trim_end()))
+                        .collect();
+                    let rows_end = rows
+                        .iter()
+                        .rposition(|(_, row)| !row.is_empty())
+                        .map_or(0, |i| i + 1);
+                    grid = Some((*directive, rows[..rows_end].to_vec()));
+                }
+                _ => return Err(directive.error(format!("Unknown directive {:?}", directive.text))),
+            }
+        }
+        let (grid_token, rows) = grid.ok_or_else(|| LevelError {
+            line: source.lines().count() + 1,
+            column: 1,
+            message: "Missing grid".to_owned(),
+        })?;
+        let size = match size {
+            Some(size) => size,
+            None => match rows.first() {
+                Some((_, row)) => vec2(row.chars().count(), rows.len()),
+                None => return Err(grid_token.error("Grid is empty")),
+            },
+        };
+        if size.x == 0 || rows.len() != size.y {
+            return Err(grid_token.error(format!(
+                "Expected {} rows of {} tiles, got {} rows",
+                size.y,
+                size.x,
+                rows.len()
+            )));
+        }
+        let mut tiles = vec![vec![Tile::Nothing; size.y]; size.x];
+        for (i, &(line, row)) in rows.iter().enumerate() {
+            let y = size.y - 1 - i;
+            let mut width = 0;
+            for (x, c) in row.chars().enumerate() {
+                let error = |message: String| LevelError {
+                    line,
+                    column: x + 1,
+                    message,
+                };
+                if x >= size.x {
+                    return Err(error(format!("Row is longer than {} tiles", size.x)));
+                }
+                tiles[x][y] = *legend
+                    .get(&c)
+                    .ok_or_else(|| error(format!("Unknown tile symbol {:?}", c)))?;
+                width += 1;
+            }
+            if width < size.x {
+                return Err(LevelError {
+                    line,
+                    column: width + 1,
+                    message: format!("Row is shorter than {} tiles", size.x),
+                });
+            }
+        }
+        let map = Map::from_tiles(tiles, peace);
+        let spawn = match spawn {
+            Some((token, spawn)) => {
+                if spawn.x < 0.0
+                    || spawn.y < 0.0
+                    || spawn.x >= size.x as f32
+                    || spawn.y >= size.y as f32
+                {
+                    return Err(token.error("Spawn is outside of the map"));
+                }
+                if map.is_solid(spawn.map(|x| x as i32)) {
+                    return Err(token.error("Spawn is inside a solid tile"));
+                }
+                spawn
+            }
+            None => size.map(|x| x as f32) / 2.0,
+        };
+        Ok(Self { map, spawn, stage })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> (usize, usize) {
+        let e = Level::parse(source).err().unwrap();
+        (e.line, e.column)
+    }
+
+    #[test]
+    fn errors_point_at_the_token() {
+        assert_eq!(error("tile x Foo\ngrid\nx"), (1, 8));
+        assert_eq!(error("tile x Food time=3\ngrid\nx"), (1, 13));
+        assert_eq!(error("grid\n...\n.x."), (3, 2));
+        assert_eq!(error("  stage Foo\ngrid\n..."), (1, 9));
+        assert_eq!(error("grid\n...\n.."), (3, 3));
+        assert_eq!(error("size 3 3\ngrid\n...\n..."), (2, 1));
+    }
+
+    #[test]
+    fn source_round_trip() {
+        let level = Level::parse(include_str!("../levels/example.level")).unwrap();
+        assert_eq!(level.stage, Stage::KillWeed);
+        let source = level.to_source().unwrap();
+        let again = Level::parse(&source).unwrap();
+        assert_eq!(again.map.tiles, level.map.tiles);
+        assert_eq!(again.spawn, level.spawn);
+        assert_eq!(again.stage, level.stage);
+        assert_eq!(again.to_source().unwrap(), source);
+    }
+}
@@ -0,0 +1,55 @@
+use super::*;
+
+pub const LANGUAGES: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.json")),
+    ("ru", include_str!("../locales/ru.json")),
+];
+
+pub struct Locale {
+    language: usize,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        let fallback = Self::parse(LANGUAGES[0].1);
+        Self {
+            language: 0,
+            strings: fallback.clone(),
+            fallback,
+        }
+    }
+}
+
+impl Locale {
+    pub fn new(language: &str) -> Result<Self, String> {
+        let mut locale = Self::default();
+        locale.set_language(
+            LANGUAGES
+                .iter()
+                .position(|&(name, _)| name == language)
+                .ok_or_else(|| format!("Unknown language {:?}", language))?,
+        );
+        Ok(locale)
+    }
+    fn parse(source: &str) -> HashMap<String, String> {
+        serde_json::from_str(source).expect("Failed to parse string table")
+    }
+    fn set_language(&mut self, language: usize) {
+        self.language = language;
+        self.strings = Self::parse(LANGUAGES[language].1);
+    }
+    pub fn next_language(&mut self) {
+        self.set_language((self.language + 1) % LANGUAGES.len());
+    }
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map_or(key, |text| text.as_str())
+    }
+    pub fn format(&self, key: &str, arg: impl Display) -> String {
+        self.get(key).replacen("{}", &arg.to_string(), 1)
+    }
+}
@@ -1,58 +1,78 @@
 #![windows_subsystem = "windows"]
 
 use geng::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use structopt::StructOpt;
 
+mod ability;
+mod audio;
+mod broadphase;
 mod camera;
+mod controls;
+mod crop;
+mod editor;
+mod event;
+mod gallery;
+mod level;
+mod locale;
 mod map;
+mod menu;
 mod mutation;
+mod options;
+mod palette;
 mod particles;
+mod pause;
 mod player;
 mod primitive;
+mod profile;
 mod projectile;
+mod replay;
+mod save;
+mod script;
+mod settings;
+mod simulation;
+mod speedrun;
+mod stress;
+mod weed;
 
+use ability::*;
+use audio::*;
+use broadphase::*;
 use camera::*;
+use controls::*;
+use crop::*;
+use editor::*;
+use event::*;
+use gallery::*;
+use level::*;
+use locale::*;
 use map::*;
+use menu::*;
 use mutation::*;
+use options::*;
+use palette::*;
 use particles::*;
+use pause::*;
 use player::*;
 use primitive::*;
+use profile::*;
 use projectile::*;
+use replay::*;
+use save::*;
+use script::*;
+use settings::*;
+use simulation::*;
+use speedrun::*;
+use stress::*;
+use weed::*;
 
 #[derive(geng::Assets)]
 pub struct Assets {
-    #[asset(path = "spit.wav")]
-    spit: geng::Sound,
-    #[asset(path = "eat.wav")]
-    eat: geng::Sound,
-    #[asset(path = "smoke.wav")]
-    smoke: geng::Sound,
-    #[asset(path = "crack.wav")]
-    crack: geng::Sound,
-    #[asset(path = "birth.wav")]
-    birth: geng::Sound,
-    #[asset(path = "poop.wav")]
-    poop: geng::Sound,
-    #[asset(path = "hit.wav")]
-    hit: geng::Sound,
-    #[asset(path = "death.wav")]
-    death: geng::Sound,
-    #[asset(path = "jump.wav")]
-    jump: geng::Sound,
-    #[asset(path = "projectile_hit.wav")]
-    projectile_hit: geng::Sound,
-    #[asset(path = "weed.wav")]
-    weed: geng::Sound,
-    #[asset(path = "weed_death.wav")]
-    weed_death: geng::Sound,
-    #[asset(path = "trophey.wav")]
-    trophey: geng::Sound,
-    #[asset(path = "win.wav")]
-    win: geng::Sound,
-    #[asset(path = "mutate.wav")]
-    mutate: geng::Sound,
+    #[asset(path = ".")]
+    sfx: SfxData,
 }
 
-#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Stage {
     Start,
     Moving,
@@ -71,407 +91,628 @@ pub enum Stage {
 }
 
 impl Stage {
-    fn help(&self) -> &str {
+    pub fn text(&self) -> &'static str {
         match self {
-            Self::Start => "Use WASD to move around",
-            Self::Moving => "Try to break the wall",
-            Self::Born => "Use Space to jump",
-            Self::ToCrush => "Crush the shell to fertilize soil",
-            Self::WaitForFood => "Fertilized soil will grow something eventually",
-            Self::Poop => "Pooping is unavoidable if you jump on empty space after eating",
-            Self::PoopFertilize => "Poop can also be used as fertilizer",
-            Self::GrowWeed => "More food! More poop! More!",
-            Self::KillWeed => "Getting rid of angry plants may require planting more",
-            Self::Mutate => "This mutated root must be destroyed!",
-            Self::GrowMutation => "Maybe mutation should be spread, make life more colorful",
-            Self::KillMutated => "Well, you've done this before",
-            Self::KillAll => "Collect all tropheys. Remember there are several levels of mutation!",
-            Self::Win => "You WON! Congrats! Make screenshot, or nobody will believe you!",
+            Self::Start => "stage.start",
+            Self::Moving => "stage.moving",
+            Self::Born => "stage.born",
+            Self::ToCrush => "stage.to_crush",
+            Self::WaitForFood => "stage.wait_for_food",
+            Self::Poop => "stage.poop",
+            Self::PoopFertilize => "stage.poop_fertilize",
+            Self::GrowWeed => "stage.grow_weed",
+            Self::KillWeed => "stage.kill_weed",
+            Self::Mutate => "stage.mutate",
+            Self::GrowMutation => "stage.grow_mutation",
+            Self::KillMutated => "stage.kill_mutated",
+            Self::KillAll => "stage.kill_all",
+            Self::Win => "stage.win",
         }
     }
 }
 
+#[derive(Debug, Clone, StructOpt)]
+pub struct Opt {
+    #[structopt(long)]
+    seed: Option<u64>,
+    #[structopt(long, parse(from_os_str))]
+    record: Option<std::path::PathBuf>,
+    #[structopt(long, parse(from_os_str))]
+    replay: Option<std::path::PathBuf>,
+    #[structopt(long, parse(from_os_str), default_value = "saves")]
+    save_dir: std::path::PathBuf,
+    #[structopt(long)]
+    load: Option<SaveSlot>,
+    #[structopt(long, parse(from_os_str))]
+    level: Option<std::path::PathBuf>,
+    #[structopt(skip = std::path::PathBuf::from("custom.level"))]
+    custom_level: std::path::PathBuf,
+    #[structopt(long, parse(from_os_str))]
+    script: Option<std::path::PathBuf>,
+    #[structopt(long, parse(from_os_str))]
+    palette: Option<std::path::PathBuf>,
+    #[structopt(long)]
+    editor: bool,
+    #[structopt(long, default_value = "en")]
+    language: String,
+    #[structopt(long)]
+    stress: Option<usize>,
+}
+
+impl Opt {
+    fn paths_mut(&mut self) -> impl Iterator<Item = &mut std::path::PathBuf> {
+        self.record
+            .iter_mut()
+            .chain(self.replay.iter_mut())
+            .chain(std::iter::once(&mut self.save_dir))
+            .chain(self.level.iter_mut())
+            .chain(std::iter::once(&mut self.custom_level))
+            .chain(self.script.iter_mut())
+            .chain(self.palette.iter_mut())
+    }
+    fn level_path(&self) -> std::path::PathBuf {
+        self.level
+            .clone()
+            .unwrap_or_else(|| self.custom_level.clone())
+    }
+    fn new_seed(&self) -> u64 {
+        self.seed.unwrap_or_else(|| global_rng().gen())
+    }
+}
+
 pub struct Game {
-    assets: Rc<Assets>,
     geng: Rc<Geng>,
+    opt: Rc<Opt>,
+    level: Level,
+    script: Rc<Script>,
     camera: Camera,
+    simulation: Simulation,
     particles: Particles,
-    map: Map,
-    player: Player,
-    stage: Stage,
     primitive: Primitive,
-    projectiles: Vec<Projectile>,
+    locale: Locale,
+    settings: Settings,
+    profile: Profile,
+    achievements: bool,
+    speedrun: Speedrun,
+    mixer: Mixer,
+    gamepads: Gamepads,
+    pointer: Pointer,
+    pause: Option<PauseMenu>,
+    editor: Option<Editor>,
+    stress: Option<Stress>,
+    recorder: Option<Recorder>,
+    playback: Option<Playback>,
+    want_jump: bool,
     restart: bool,
 }
 
 impl Game {
-    fn new(geng: &Rc<Geng>, assets: &Rc<Assets>) -> Self {
-        let map = Map::new();
+    fn new(geng: &Rc<Geng>, assets: &Rc<Assets>, opt: &Rc<Opt>) -> Self {
+        let playback = opt.replay.as_ref().and_then(|path| {
+            Playback::open(path)
+                .map_err(|e| eprintln!("Failed to open replay {}: {}", path.display(), e))
+                .ok()
+        });
+        let palette = match (playback.as_ref().and_then(|p| p.palette()), &opt.palette) {
+            (Some(source), _) => {
+                Palette::parse(source).map_err(|e| format!("Replay palette: {}", e))
+            }
+            (None, Some(path)) => Palette::load(path),
+            (None, None) => Ok(Palette::default()),
+        };
+        palette
+            .unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                Palette::default()
+            })
+            .install();
+        let seed = match &playback {
+            Some(playback) => playback.seed(),
+            None => opt.new_seed(),
+        };
+        let level = match (&opt.level, opt.stress) {
+            (_, Some(_)) => Stress::level(),
+            (Some(path), None) => Level::load(path).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                Level::default()
+            }),
+            (None, None) => Level::default(),
+        };
+        let script = Rc::new(opt.script.as_ref().map_or_else(Script::default, |path| {
+            Script::load(path).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                Script::default()
+            })
+        }));
+        let run_key = format!("{}-{}", level_key(&level), content_key(&*script));
+        if let Some(recorded) = playback.as_ref().and_then(|p| p.level()) {
+            if recorded != run_key {
+                eprintln!("The replay was recorded on a different level or script, pass the same --level and --script to watch it");
+                std::process::exit(1);
+            }
+        }
+        let recorder = match (&opt.record, opt.load) {
+            (Some(_), Some(_)) => {
+                eprintln!("Replays always start from a new game, --record is ignored with --load");
+                None
+            }
+            (Some(path), None) => {
+                Recorder::create(path, seed, Palette::current().source(), &run_key)
+                    .map_err(|e| eprintln!("Failed to create replay {}: {}", path.display(), e))
+                    .ok()
+            }
+            (None, _) => None,
+        };
+        let save = opt.load.and_then(|slot| {
+            SaveData::read(&opt.save_dir, slot)
+                .map_err(|e| eprintln!("Failed to load {:?}, starting a new game: {}", slot, e))
+                .ok()
+        });
+        let loaded = save.is_some();
+        let simulation = match save {
+            Some(save) => Simulation::load(save, script.clone()),
+            None => Simulation::new(level.clone(), script.clone(), seed),
+        };
+        let mut speedrun = Speedrun::new(&opt.save_dir, &level, simulation.seed);
+        if loaded || playback.is_some() {
+            speedrun.unrank();
+        }
         let mut camera = Camera::new(0.1);
-        camera.center = map.size().map(|x| x as f32) / 2.0;
-        let player = Player::new(camera.center);
+        camera.center = simulation.spawn;
+        let editor = if opt.editor && recorder.is_none() && playback.is_none() {
+            Some(Editor::new(geng, level.clone(), opt.level_path()))
+        } else {
+            None
+        };
+        let profile = Profile::load(&opt.save_dir);
+        if let Err(e) = &profile {
+            eprintln!("{}, achievements are off for this session", e);
+        }
+        let achievements = profile.is_ok()
+            && opt.level.is_none()
+            && opt.script.is_none()
+            && opt.palette.is_none()
+            && opt.stress.is_none()
+            && playback.is_none()
+            && editor.is_none();
         Self {
-            assets: assets.clone(),
             geng: geng.clone(),
+            opt: opt.clone(),
+            level,
+            script,
             camera,
+            simulation,
             particles: Particles::new(),
-            map,
-            player,
-            stage: Stage::Start,
             primitive: Primitive::new(geng),
-            projectiles: Vec::new(),
+            locale: Locale::new(&opt.language).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                Locale::default()
+            }),
+            settings: Settings::load(&opt.save_dir),
+            profile: profile.unwrap_or_default(),
+            achievements,
+            speedrun,
+            mixer: Mixer::new(assets),
+            gamepads: Gamepads::new(),
+            pointer: Pointer::new(),
+            pause: None,
+            editor,
+            stress: opt.stress.map(Stress::new),
+            recorder,
+            playback,
+            want_jump: false,
             restart: false,
         }
     }
-    fn text_at(&self, pos: Vec2<f32>) -> String {
-        if let Some(text) = self.map.text_at(pos) {
-            return text;
-        }
-        if (self.player.pos - pos).len() < self.player.radius {
-            return "YOU".to_owned();
-        }
-        "Nothing".to_owned()
+    fn restart(&mut self, seed: u64) {
+        self.simulation = Simulation::new(self.level.clone(), self.script.clone(), seed);
+        self.speedrun.reset(&self.level, seed);
+        if self.playback.is_some() {
+            self.speedrun.unrank();
+        }
+        self.particles = Particles::new();
+        self.camera = Camera::new(0.1);
+        self.camera.center = self.simulation.spawn;
     }
-}
-
-impl geng::State for Game {
-    fn update(&mut self, delta_time: f64) {
-        self.player.stage = self.stage;
-        let delta_time = delta_time as f32;
-        self.camera.target_fov = if self.stage == Stage::Start || self.stage == Stage::Win {
-            5.0
-        } else {
-            max(self.map.size().x, self.map.size().y) as f32
-                + if self.stage == Stage::Moving {
-                    5.0
-                } else {
-                    2.0
-                }
-        };
-        if self.stage == Stage::Win {
-            self.camera.center = self.player.pos;
-        }
-        self.camera.update(delta_time);
-        if self.stage == Stage::Win {
+    fn toggle_editor(&mut self) {
+        if self.recorder.is_some() || self.playback.is_some() {
             return;
         }
-        self.map.update(
-            delta_time,
-            &mut self.particles,
-            &mut self.projectiles,
-            &mut self.player,
-            &self.assets,
-        );
-        self.player.target_vel = vec2(0.0, 0.0);
-        if self.geng.window().is_key_pressed(geng::Key::W) {
-            self.player.target_vel.y += 1.0;
+        match self.editor.take() {
+            Some(editor) => {
+                self.level = editor.level;
+                self.achievements = false;
+                self.restart(self.opt.new_seed());
+            }
+            None => {
+                self.editor = Some(Editor::new(
+                    &self.geng,
+                    self.level.clone(),
+                    self.opt.level_path(),
+                ));
+            }
         }
-        if self.geng.window().is_key_pressed(geng::Key::A) {
-            self.player.target_vel.x -= 1.0;
+    }
+    fn save(&self, slot: SaveSlot) {
+        if let Err(e) = self.simulation.save().write(&self.opt.save_dir, slot) {
+            eprintln!("Failed to save {:?}: {}", slot, e);
         }
-        if self.geng.window().is_key_pressed(geng::Key::S) {
-            self.player.target_vel.y -= 1.0;
+    }
+    fn load(&mut self, slot: SaveSlot) {
+        if self.recorder.is_some() || self.playback.is_some() {
+            return;
         }
-        if self.geng.window().is_key_pressed(geng::Key::D) {
-            self.player.target_vel.x += 1.0;
+        match SaveData::read(&self.opt.save_dir, slot) {
+            Ok(save) => {
+                self.simulation = Simulation::load(save, self.script.clone());
+                self.speedrun.reset(&self.level, self.simulation.seed);
+                self.speedrun.unrank();
+                self.particles = Particles::new();
+                self.camera.center = self.simulation.spawn;
+            }
+            Err(e) => eprintln!("Failed to load {:?}: {}", slot, e),
         }
-        if self.player.target_vel.len() > 1e-5 {
-            self.player.target_vel = self.player.target_vel.normalize();
+    }
+    fn handle_action(&mut self, action: Action) {
+        if let Some(pause) = &self.pause {
+            if action == Action::Pause && pause.at_root() {
+                self.pause = None;
+            }
+            return;
         }
-        self.player.update(delta_time, &self.assets);
-        if self.stage == Stage::Born && self.player.jump.is_some() {
-            self.stage = Stage::ToCrush;
+        match action {
+            Action::Pause => self.pause = Some(PauseMenu::new()),
+            _ if self.editor.is_some() => {}
+            Action::Jump => self.want_jump = true,
+            Action::Restart => self.restart = true,
+            Action::MoveUp | Action::MoveDown | Action::MoveLeft | Action::MoveRight => {}
         }
-        if self.stage == Stage::ToCrush
-            && self.map.find(|tile| {
-                if let Tile::FertilizedSoil { .. } = tile {
-                    true
-                } else {
-                    false
-                }
-            }) > 0
-        {
-            self.stage = Stage::WaitForFood;
+    }
+    fn live_frame(&mut self, delta_time: f32) -> Frame {
+        let controls = &self.settings.controls;
+        let player = &self.simulation.player;
+        let mut move_direction =
+            controls.move_direction(&self.geng.window()) + self.gamepads.stick(controls.dead_zone);
+        let mut jump = mem::replace(&mut self.want_jump, false);
+        if let Some(click) = self.pointer.click.take() {
+            if controls.pointer && (click - player.pos).len() < player.radius {
+                jump = true;
+            }
         }
-        if self.stage == Stage::WaitForFood && self.map.find(|tile| tile.is_food()) > 0 {
-            self.stage = Stage::Poop;
+        if controls.pointer {
+            move_direction += self.pointer.move_direction(player.pos, player.radius);
         }
-        if self.stage == Stage::Poop && self.map.find(|tile| tile.is_poop()) > 0 {
-            self.stage = Stage::PoopFertilize;
+        Frame {
+            delta_time,
+            input: Input {
+                move_direction,
+                jump,
+            },
+            restart: if mem::replace(&mut self.restart, false) {
+                Some(self.opt.new_seed())
+            } else {
+                None
+            },
         }
-        if self.stage == Stage::PoopFertilize && self.map.find(|tile| tile.is_poop()) == 0 {
-            self.stage = Stage::GrowWeed;
+    }
+}
+
+impl geng::State for Game {
+    fn update(&mut self, delta_time: f64) {
+        self.mixer.update(delta_time);
+        for action in self.gamepads.poll() {
+            self.handle_action(action);
         }
-        if self.stage == Stage::GrowWeed
-            && self.map.find(|tile| match tile {
-                Tile::AngryWeed { .. } => true,
-                _ => false,
-            }) > 0
-        {
-            self.stage = Stage::KillWeed;
+        if self.pause.is_some() {
+            return;
         }
-        if self.stage == Stage::KillWeed && self.map.find(|tile| *tile == Tile::MutatedRoot) > 0 {
-            self.stage = Stage::Mutate;
+        if let Some(editor) = &mut self.editor {
+            editor.update(delta_time as f32);
+            return;
         }
-        if self.stage == Stage::Mutate && self.player.mutation.is_some() {
-            self.stage = Stage::GrowMutation;
+        let mut frame = self.live_frame(delta_time as f32);
+        if let Some(playback) = &mut self.playback {
+            match playback.next_frame() {
+                Some(replay_frame) => frame = replay_frame,
+                None => self.playback = None,
+            }
         }
-        if self.stage == Stage::GrowMutation
-            && self.map.find(|tile| match tile {
-                Tile::AngryWeed { mutation, .. } if mutation.is_some() => true,
-                _ => false,
-            }) > 0
-        {
-            self.stage = Stage::KillMutated;
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.record(&frame) {
+                eprintln!("Failed to record replay, recording stopped: {}", e);
+                self.recorder = None;
+            }
         }
-        if self.stage == Stage::KillMutated && self.map.find(|tile| tile.is_trophey()) > 0 {
-            self.stage = Stage::KillAll;
+        if let Some(seed) = frame.restart {
+            self.restart(seed);
         }
-        if self.player.tropheys.len() == 7 {
-            self.assets.win.play();
-            self.stage = Stage::Win;
+        let delta_time = frame.delta_time;
+        let stage = self.simulation.stage;
+        let map_size = self.simulation.map.size();
+        self.camera.target_fov = if stage == Stage::Start || stage == Stage::Win {
+            5.0
+        } else {
+            max(map_size.x, map_size.y) as f32 + if stage == Stage::Moving { 5.0 } else { 2.0 }
+        };
+        if stage == Stage::Win {
+            self.camera.center = self.simulation.player.pos;
         }
-        if !self.player.eaten {
-            if let Tile::Food { mutation } =
-                self.map.tiles[self.player.pos.x as usize][self.player.pos.y as usize]
-            {
-                self.assets.eat.play();
-                self.player.eaten = true;
-                self.player.almost_dead = false;
-                if let Some(mutation) = mutation {
-                    self.player.mutation = mutation.mix(self.player.mutation);
-                }
-                self.map.tiles[self.player.pos.x as usize][self.player.pos.y as usize] =
-                    Tile::FertilizedSoil {
-                        time: FERTILIZED_SOIL_TIME,
-                        mutation,
-                    };
+        self.camera.update(delta_time);
+        let timer = Timer::new();
+        self.simulation.update(delta_time, frame.input);
+        if let Some(stress) = &mut self.stress {
+            stress.update(&mut self.simulation, timer.elapsed());
+        }
+        let mut profile_changed = false;
+        for event in mem::replace(&mut self.simulation.events, Vec::new()) {
+            if self.achievements {
+                profile_changed |= self.profile.handle_event(&event, &self.simulation.stats);
             }
-        }
-        if self.player.landed() {
-            if self.player.eaten
-                && self.map.tiles[self.player.pos.x as usize][self.player.pos.y as usize]
-                    == Tile::Nothing
-            {
-                self.assets.poop.play();
-                self.player.eaten = false;
-                self.assets.smoke.play();
-                self.particles.boom(self.player.pos, self.player.mutation);
-                self.map.tiles[self.player.pos.x as usize][self.player.pos.y as usize] =
-                    Tile::Poop {
-                        mutation: self.player.mutation,
-                    };
-                self.player.mutation = None;
-            } else {
-                self.map.land(
-                    self.player.pos,
-                    &mut self.particles,
-                    &mut self.player,
-                    &self.assets,
-                );
+            let pan = event.pos().map_or(0.0, |pos| {
+                (pos.x - self.camera.center.x) / self.camera.fov * 2.0
+            });
+            for &sfx in event.sounds() {
+                self.mixer
+                    .play(&self.settings.audio, sfx, SoundCategory::Sfx, pan);
             }
-        }
-        if self.stage == Stage::Start && (self.player.pos - self.camera.center).len() > 1.0 {
-            self.stage = Stage::Moving;
-        }
-        let mut fix_pos = self.player.pos;
-        if fix_pos.x < self.player.radius {
-            fix_pos.x = self.player.radius;
-        }
-        if fix_pos.y < self.player.radius {
-            fix_pos.y = self.player.radius;
-        }
-        if fix_pos.x > self.map.size().x as f32 - self.player.radius {
-            fix_pos.x = self.map.size().x as f32 - self.player.radius;
-        }
-        if fix_pos.y > self.map.size().y as f32 - self.player.radius {
-            fix_pos.y = self.map.size().y as f32 - self.player.radius;
-        }
-        if fix_pos != self.player.pos {
-            self.player.pos = fix_pos;
-            if self.player.vel.len() > self.player.max_speed / 2.0 && self.stage == Stage::Moving {
-                self.assets.crack.play();
-                self.assets.smoke.play();
-                self.particles.boom(self.player.pos, None);
-                let mut shell_pos = Vec::new();
-                for dx in -1..=1 {
-                    for dy in -1..=1 {
-                        let x = self.player.pos.x as i32 + dx;
-                        let y = self.player.pos.y as i32 + dy;
-                        if x >= 0
-                            && x < self.map.size().x as _
-                            && y >= 0
-                            && y < self.map.size().y as _
-                        {
-                            shell_pos.push(vec2(x as usize, y as usize));
-                        }
-                    }
+            self.particles.handle_event(&event);
+            if let GameEvent::StageChanged { stage } = event {
+                profile_changed |= self.achievements;
+                if self.playback.is_none() && self.stress.is_none() {
+                    self.save(SaveSlot::Auto);
                 }
-                use rand::seq::SliceRandom;
-                shell_pos.shuffle(&mut global_rng());
-                for pos in shell_pos {
-                    if self.map.tiles[pos.x][pos.y] == Tile::Nothing {
-                        self.map.tiles[pos.x][pos.y] = Tile::BrokenShell;
-                        self.particles.boom(pos.map(|x| x as f32 + 0.5), None);
-                        break;
-                    }
-                }
-                if self
-                    .map
-                    .tiles
-                    .iter()
-                    .map(|row| {
-                        row.iter()
-                            .filter(|tile| **tile == Tile::BrokenShell)
-                            .count()
-                    })
-                    .sum::<usize>()
-                    == 3
-                {
-                    self.player.radius = 0.3;
-                    self.assets.birth.play();
-                    self.stage = Stage::Born;
-                }
-            }
-            self.player.vel = vec2(0.0, 0.0);
-        }
-        self.particles.update(delta_time);
-        for i in 0..self.projectiles.len() {
-            for j in 0..i {
-                if (self.projectiles[i].pos - self.projectiles[j].pos).len()
-                    < self.projectiles[i].radius + self.projectiles[j].radius
-                    && self.projectiles[i].mutation == self.projectiles[j].mutation
-                {
-                    self.projectiles[i].alive = false;
-                    self.projectiles[j].alive = false;
+                if self.speedrun.split(stage, self.simulation.stats.time) {
+                    self.speedrun.save(&self.opt.save_dir);
                 }
             }
         }
-        for p in &mut self.projectiles {
-            if self.player.alive && (p.pos - self.player.pos).len() < p.radius + self.player.radius
-            {
-                p.alive = false;
-                if self.player.almost_dead {
-                    self.assets.death.play();
-                    self.particles.boom(self.player.pos, self.player.mutation);
-                    self.player.alive = false;
-                } else {
-                    self.assets.hit.play();
-                    self.player.almost_dead = true;
-                    self.particles.boom(p.pos, p.mutation);
-                    self.player.vel += p.vel * 2.0;
-                }
-            }
-            self.map.collide_projectile(p, &self.assets);
-            p.update(delta_time);
-            if p.pos.x < 0.0
-                || p.pos.y < 0.0
-                || p.pos.x >= self.map.size().x as f32
-                || p.pos.y >= self.map.size().y as f32
-            {
-                p.alive = false;
-            }
-            if !p.alive {
-                self.assets.projectile_hit.play();
-                self.assets.smoke.play();
-                self.particles.boom(p.pos, p.mutation);
-            }
+        if profile_changed {
+            self.profile.save(&self.opt.save_dir);
         }
-        self.projectiles.retain(|p| p.alive);
+        self.particles.update(delta_time);
     }
     fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        let screen_mouse_pos = self.geng.window().mouse_pos().map(|x| x as f32);
+        if let Some(editor) = &mut self.editor {
+            editor.draw(framebuffer, &self.primitive, &self.locale);
+            if let Some(pause) = &mut self.pause {
+                pause.draw(
+                    framebuffer,
+                    &self.camera,
+                    &self.primitive,
+                    screen_mouse_pos,
+                    &self.locale,
+                    &self.settings,
+                    &self.profile,
+                );
+                self.primitive.flush(framebuffer, &self.camera);
+            }
+            return;
+        }
         ugli::clear(framebuffer, Some(Color::WHITE), None);
-        self.map
-            .draw(framebuffer, &self.camera, &self.primitive, self.stage);
-        self.player.draw(
+        self.pointer.update(framebuffer, &self.camera);
+        let simulation = &self.simulation;
+        simulation
+            .map
+            .draw(framebuffer, &self.camera, &self.primitive, simulation.stage);
+        if self.settings.display.fertility {
+            simulation
+                .map
+                .draw_fertility(framebuffer, &self.camera, &self.primitive);
+        }
+        simulation.player.draw(
             framebuffer,
             &self.camera,
             &self.primitive,
-            if self.stage < Stage::Born {
-                Some(
-                    self.map
-                        .tiles
-                        .iter()
-                        .map(|row| {
-                            row.iter()
-                                .filter(|tile| **tile == Tile::BrokenShell)
-                                .count()
-                        })
-                        .sum::<usize>(),
-                )
+            if simulation.stage < Stage::Born {
+                Some(simulation.broken_shells())
             } else {
                 None
             },
         );
-        for p in &self.projectiles {
+        for p in &simulation.projectiles {
             p.draw(framebuffer, &self.camera, &self.primitive);
         }
-        self.particles
-            .draw(framebuffer, &self.camera, &self.primitive);
+        if self.settings.display.particles {
+            self.particles
+                .draw(framebuffer, &self.camera, &self.primitive);
+        }
 
-        let mouse_pos = self.camera.screen_to_world(
-            framebuffer,
-            self.geng.window().mouse_pos().map(|x| x as f32),
-        );
-        let help_pos = self.camera.screen_to_world(framebuffer, vec2(0.0, 0.0))
-            + vec2(self.camera.fov / 20.0, 0.0);
+        let text_scale = self.settings.display.text_scale;
+        let mouse_pos = self.camera.screen_to_world(framebuffer, screen_mouse_pos);
+        let help_size = self.camera.fov / 20.0 * text_scale;
+        let help_pos =
+            self.camera.screen_to_world(framebuffer, vec2(0.0, 0.0)) + vec2(help_size, 0.0);
         self.primitive.text_bubble(
             framebuffer,
             &self.camera,
             "?".to_owned(),
             help_pos,
-            self.camera.fov / 20.0,
+            help_size,
         );
-        let text = if (mouse_pos - help_pos - vec2(0.0, self.camera.fov * 3.0 / 40.0)).len()
-            < self.camera.fov / 20.0
-        {
-            let mut text = self.stage.help().to_owned();
-            if !self.player.alive {
-                text += " (press R to restart)";
+        let text = if (mouse_pos - help_pos - vec2(0.0, help_size * 1.5)).len() < help_size {
+            let controls = &self.settings.controls;
+            let keys: Vec<String> = simulation
+                .help_keys()
+                .iter()
+                .map(|&action| controls.keys_text(action))
+                .collect();
+            let mut text = self.locale.format(simulation.help(), keys.join(" / "));
+            if !simulation.player.alive {
+                text += " ";
+                text += &self
+                    .locale
+                    .format("hint.restart", controls.keys_text(Action::Restart));
             }
             text
         } else {
-            self.text_at(mouse_pos)
+            self.locale.get(simulation.text_at(mouse_pos)).to_owned()
         };
-        self.primitive.text_bubble(
-            framebuffer,
-            &self.camera,
-            text,
-            mouse_pos,
-            self.camera.fov / 30.0,
-        );
+        let abilities = simulation
+            .player
+            .mutation
+            .map_or_else(Vec::new, |mutation| mutation.abilities());
+        if !abilities.is_empty() {
+            let abilities: Vec<&str> = abilities
+                .iter()
+                .map(|ability| self.locale.get(ability.text()))
+                .collect();
+            self.primitive.text_bubble(
+                framebuffer,
+                &self.camera,
+                self.locale.format("hud.abilities", abilities.join(", ")),
+                self.camera.center - vec2(0.0, self.camera.fov * 0.45),
+                self.camera.fov / 30.0 * text_scale,
+            );
+        }
+        if self.settings.display.timer {
+            let size = self.camera.fov / 30.0 * text_scale;
+            let corner = self
+                .camera
+                .screen_to_world(framebuffer, framebuffer.size().map(|x| x as f32));
+            for (i, line) in self
+                .speedrun
+                .lines(simulation.stats.time, &self.locale)
+                .into_iter()
+                .enumerate()
+            {
+                self.primitive.text_bubble(
+                    framebuffer,
+                    &self.camera,
+                    line,
+                    corner - vec2(size, size * (i as f32 * 2.5 + 3.0)),
+                    size,
+                );
+            }
+        }
+        if let Some(stress) = &self.stress {
+            self.primitive.text_bubble(
+                framebuffer,
+                &self.camera,
+                stress.text(simulation),
+                self.camera.center + vec2(0.0, self.camera.fov * 0.4),
+                self.camera.fov / 30.0 * text_scale,
+            );
+        }
+        match &mut self.pause {
+            Some(pause) => pause.draw(
+                framebuffer,
+                &self.camera,
+                &self.primitive,
+                screen_mouse_pos,
+                &self.locale,
+                &self.settings,
+                &self.profile,
+            ),
+            None => self.primitive.text_bubble(
+                framebuffer,
+                &self.camera,
+                text,
+                mouse_pos,
+                self.camera.fov / 30.0 * text_scale,
+            ),
+        }
         self.primitive.flush(framebuffer, &self.camera);
     }
     fn handle_event(&mut self, event: geng::Event) {
+        self.pointer.handle_event(
+            &event,
+            self.settings.controls.pointer && self.pause.is_none() && self.editor.is_none(),
+        );
+        match event {
+            geng::Event::KeyDown { key: geng::Key::F5 } => {
+                self.toggle_editor();
+                return;
+            }
+            geng::Event::KeyDown { key: geng::Key::F6 } => {
+                self.locale.next_language();
+                return;
+            }
+            _ => {}
+        }
+        let action = match event {
+            geng::Event::KeyDown { key } => self.settings.controls.action(key),
+            _ => None,
+        };
+        if let Some(pause) = &mut self.pause {
+            let at_root = pause.at_root();
+            match pause.handle_event(&event, &mut self.settings) {
+                Some(PauseAction::Resume) => self.pause = None,
+                Some(PauseAction::Restart) => {
+                    self.restart = true;
+                    self.pause = None;
+                }
+                Some(PauseAction::SettingsChanged) => {
+                    self.settings.save(&self.opt.save_dir);
+                    self.mixer
+                        .play(&self.settings.audio, Sfx::Jump, SoundCategory::Ui, 0.0);
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                Some(PauseAction::Quit) => {
+                    if self.achievements {
+                        self.profile.save(&self.opt.save_dir);
+                    }
+                    std::process::exit(0)
+                }
+                None if at_root && action == Some(Action::Pause) => self.pause = None,
+                None => {}
+            }
+            return;
+        }
+        if action == Some(Action::Pause) {
+            self.handle_action(Action::Pause);
+            return;
+        }
+        if let Some(editor) = &mut self.editor {
+            editor.handle_event(event);
+            return;
+        }
+        if let Some(action) = action {
+            self.handle_action(action);
+            return;
+        }
         match event {
             geng::Event::KeyDown { key } => match key {
-                geng::Key::Space => {
-                    self.player.want_jump = true;
+                geng::Key::F1 | geng::Key::F2 | geng::Key::F3 | geng::Key::F4 => {
+                    let slot = SaveSlot::Manual(match key {
+                        geng::Key::F1 => 1,
+                        geng::Key::F2 => 2,
+                        geng::Key::F3 => 3,
+                        _ => 4,
+                    });
+                    if self.geng.window().is_key_pressed(geng::Key::LShift)
+                        || self.geng.window().is_key_pressed(geng::Key::RShift)
+                    {
+                        self.load(slot);
+                    } else {
+                        self.save(slot);
+                    }
                 }
-                geng::Key::R => {
-                    self.restart = true;
+                geng::Key::F9 => {
+                    self.load(SaveSlot::Auto);
+                }
+                geng::Key::F8 => {
+                    if let Err(e) = self.speedrun.export(&self.opt.save_dir) {
+                        eprintln!("Failed to export splits: {}", e);
+                    }
                 }
                 _ => {}
             },
             _ => {}
         }
     }
-    fn transition(&mut self) -> Option<geng::Transition> {
-        if self.restart {
-            Some(geng::Transition::Switch(Box::new(Game::new(
-                &self.geng,
-                &self.assets,
-            ))))
-        } else {
-            None
-        }
-    }
 }
 
 fn main() {
+    let mut opt = Opt::from_args();
     if let Ok(dir) = std::env::var("CARGO_MANIFEST_DIR") {
+        let cwd = std::env::current_dir().unwrap();
+        for path in opt.paths_mut() {
+            *path = cwd.join(&path);
+        }
         std::env::set_current_dir(std::path::Path::new(&dir).join("static")).unwrap();
     }
+    let opt = Rc::new(opt);
     let geng = Rc::new(Geng::new(geng::ContextOptions {
         title: "Egg Farm".to_owned(),
         ..default()
@@ -484,7 +725,7 @@ fn main() {
             let geng = geng.clone();
             move |assets| {
                 let assets = Rc::new(assets.unwrap());
-                Game::new(&geng, &assets)
+                Game::new(&geng, &assets, &opt)
             }
         },
     );
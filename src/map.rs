@@ -1,6 +1,6 @@
 use super::*;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Tile {
     Nothing,
     BrokenShell,
@@ -8,6 +8,8 @@ pub enum Tile {
     FertilizedSoil {
         time: f32,
         mutation: Option<Mutation>,
+        #[serde(default)]
+        crop: CropSpecies,
     },
     Food {
         mutation: Option<Mutation>,
@@ -18,50 +20,97 @@ pub enum Tile {
     AngryWeed {
         time: f32,
         mutation: Option<Mutation>,
+        #[serde(default)]
+        species: WeedSpecies,
     },
     MutatedRoot,
+    Bleach {
+        mutation: Option<Mutation>,
+    },
     Trophey {
         mutation: Mutation,
     },
+    Wall,
+    Rock,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TileKind {
+    Nothing,
+    BrokenShell,
+    CrushedShell,
+    FertilizedSoil,
+    Food,
+    Poop,
+    AngryWeed,
+    MutatedRoot,
+    Bleach,
+    Trophey,
+    Wall,
+    Rock,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct SharedState {
     peace: usize,
 }
 
 pub const ANGRY_WEED_SHOOT_TIME: f32 = 3.0;
 pub const FERTILIZED_SOIL_TIME: f32 = 3.0;
+pub const BASE_FERTILITY: f32 = 1.0;
+pub const MAX_FERTILITY: f32 = 3.0;
+const WEED_SEED_RATE: f32 = 0.05;
+const POLLINATE_RATE: f32 = 0.2;
+const ROOT_SPREAD_RATE: f32 = 0.02;
 
 impl Tile {
-    pub fn is_trophey(&self) -> bool {
+    pub fn kind(&self) -> TileKind {
         match self {
-            Self::Trophey { .. } => true,
-            _ => false,
+            Self::Nothing => TileKind::Nothing,
+            Self::BrokenShell => TileKind::BrokenShell,
+            Self::CrushedShell => TileKind::CrushedShell,
+            Self::FertilizedSoil { .. } => TileKind::FertilizedSoil,
+            Self::Food { .. } => TileKind::Food,
+            Self::Poop { .. } => TileKind::Poop,
+            Self::AngryWeed { .. } => TileKind::AngryWeed,
+            Self::MutatedRoot => TileKind::MutatedRoot,
+            Self::Bleach { .. } => TileKind::Bleach,
+            Self::Trophey { .. } => TileKind::Trophey,
+            Self::Wall => TileKind::Wall,
+            Self::Rock => TileKind::Rock,
         }
     }
-    pub fn is_food(&self) -> bool {
-        match self {
-            Self::Food { .. } => true,
-            _ => false,
+    pub fn mutation(&self) -> Option<Mutation> {
+        match *self {
+            Self::FertilizedSoil { mutation, .. }
+            | Self::Food { mutation }
+            | Self::Poop { mutation }
+            | Self::Bleach { mutation }
+            | Self::AngryWeed { mutation, .. } => mutation,
+            Self::Trophey { mutation } => Some(mutation),
+            _ => None,
         }
     }
-    pub fn is_poop(&self) -> bool {
+    pub fn is_solid(&self) -> bool {
         match self {
-            Self::Poop { .. } => true,
+            Self::Wall | Self::Rock => true,
             _ => false,
         }
     }
-    fn text(&self) -> String {
+    pub fn text(&self) -> &'static str {
         match self {
-            Self::Nothing => "Nothing".to_owned(),
-            Self::BrokenShell => "Broken shell".to_owned(),
-            Self::CrushedShell => "Crushed shell".to_owned(),
-            Self::FertilizedSoil { .. } => "Fertilized soil".to_owned(),
-            Self::Food { .. } => "Food".to_owned(),
-            Self::Poop { .. } => "Poop".to_owned(),
-            Self::AngryWeed { .. } => "Angry weed".to_owned(),
-            Self::MutatedRoot => "Mutated root".to_owned(),
-            Self::Trophey { .. } => "Trophey".to_owned(),
+            Self::Nothing => "tile.nothing",
+            Self::BrokenShell => "tile.broken_shell",
+            Self::CrushedShell => "tile.crushed_shell",
+            Self::FertilizedSoil { time, crop, .. } => crop.text(crop.stage(*time)),
+            Self::Food { .. } => "tile.food",
+            Self::Poop { .. } => "tile.poop",
+            Self::AngryWeed { species, .. } => species.text(),
+            Self::MutatedRoot => "tile.mutated_root",
+            Self::Bleach { .. } => "tile.bleach",
+            Self::Trophey { .. } => "tile.trophey",
+            Self::Wall => "tile.wall",
+            Self::Rock => "tile.rock",
         }
     }
     fn update(
@@ -69,138 +118,275 @@ impl Tile {
         delta_time: f32,
         shared: &mut SharedState,
         pos: Vec2<usize>,
+        fertility: f32,
         projectiles: &mut Vec<Projectile>,
         player: &mut Player,
-        assets: &Assets,
-    ) -> Option<Option<Mutation>> {
+        rng: &mut StdRng,
+        events: &mut Vec<GameEvent>,
+        harvest: &mut Vec<(Vec2<usize>, Option<Mutation>)>,
+    ) {
         match self {
-            Self::FertilizedSoil { time, mutation } => {
+            Self::FertilizedSoil {
+                time,
+                mutation,
+                crop,
+            } => {
                 let mutation = *mutation;
-                *time -= delta_time;
+                let crop = *crop;
+                *time -= delta_time * (0.5 + fertility * 0.5);
                 if *time <= 0.0 {
+                    let cell = pos;
+                    let pos = pos.map(|x| x as f32 + 0.5);
                     if shared.peace > 0 {
                         shared.peace -= 1;
                         *self = Self::Food { mutation };
+                        events.push(GameEvent::FoodGrown { pos, mutation });
                     } else {
                         let options = [
-                            (3, Self::Food { mutation }),
+                            (1.0 + fertility * 2.0, Self::Food { mutation }),
                             (
-                                1,
+                                1.0,
                                 Self::AngryWeed {
                                     time: ANGRY_WEED_SHOOT_TIME,
                                     mutation,
+                                    species: WeedSpecies::Angry,
                                 },
                             ),
                         ];
                         let mut rand =
-                            global_rng().gen_range(0..options.iter().map(|&(w, _)| w).sum::<i32>());
+                            rng.gen_range(0.0..options.iter().map(|&(w, _)| w).sum::<f32>());
                         for &(w, option) in &options {
                             if rand < w {
-                                if let Self::AngryWeed { .. } = option {
-                                    assets.weed.play();
-                                }
-                                *self = option;
+                                *self = if let Self::AngryWeed { .. } = option {
+                                    events.push(GameEvent::WeedSprouted { pos, mutation });
+                                    let species = WeedSpecies::sprout(mutation, rng);
+                                    Self::AngryWeed {
+                                        time: species.shoot_time(),
+                                        mutation,
+                                        species,
+                                    }
+                                } else {
+                                    events.push(GameEvent::FoodGrown { pos, mutation });
+                                    option
+                                };
                                 break;
                             }
                             rand -= w;
                         }
                     }
-                    return Some(mutation);
+                    if let Self::Food { .. } = self {
+                        for _ in 1..crop.harvest() {
+                            harvest.push((cell, mutation));
+                        }
+                    }
                 }
             }
-            Self::AngryWeed { time, mutation } => {
+            Self::AngryWeed {
+                time,
+                mutation,
+                species,
+            } => {
                 let pos = pos.map(|x| x as f32 + 0.5);
                 let player_dist = (pos - player.pos).len();
                 *time -= delta_time;
                 if player_dist < 0.8 && player_dist > 1e-5 {
                     player.pos = pos + (player.pos - pos).normalize() * 0.8;
                 }
-                if *time < 0.0 {
-                    *time = ANGRY_WEED_SHOOT_TIME;
-                    if (player.pos - pos).len() > 1e-5 {
-                        assets.spit.play();
-                        projectiles.push(Projectile::new(
-                            pos,
-                            0.2,
-                            (player.pos - pos).normalize() * 3.0,
-                            *mutation,
-                        ));
-                    }
+                species.update(time, pos, *mutation, player, rng, projectiles, events);
+            }
+            _ => {}
+        }
+    }
+    fn react(
+        &self,
+        pos: Vec2<usize>,
+        neighbours: &[Option<Tile>; 4],
+        delta_time: f32,
+        shared: &SharedState,
+        rng: &mut StdRng,
+        events: &mut Vec<GameEvent>,
+    ) -> Option<Tile> {
+        let chance = |rate: f32, count: usize| 1.0 - (-rate * count as f32 * delta_time).exp();
+        let pos = pos.map(|x| x as f32 + 0.5);
+        match *self {
+            Self::FertilizedSoil { mutation, .. } if shared.peace == 0 => {
+                let weeds = neighbours
+                    .iter()
+                    .flatten()
+                    .filter(|tile| tile.kind() == TileKind::AngryWeed)
+                    .count();
+                if weeds > 0 && rng.gen_bool(chance(WEED_SEED_RATE, weeds) as f64) {
+                    events.push(GameEvent::WeedSprouted { pos, mutation });
+                    let species = WeedSpecies::sprout(mutation, rng);
+                    return Some(Self::AngryWeed {
+                        time: species.shoot_time(),
+                        mutation,
+                        species,
+                    });
+                }
+            }
+            Self::Food { mutation } => {
+                let pollen = || {
+                    neighbours.iter().flatten().filter_map(|tile| match *tile {
+                        Self::Food {
+                            mutation: Some(other),
+                        } if other.mix(mutation) != mutation => Some(other),
+                        _ => None,
+                    })
+                };
+                let count = pollen().count();
+                if count > 0 && rng.gen_bool(chance(POLLINATE_RATE, count) as f64) {
+                    let other = pollen().nth(rng.gen_range(0..count)).unwrap();
+                    let mutation = other.mix(mutation);
+                    events.push(GameEvent::FoodPollinated { pos, mutation });
+                    return Some(Self::Food { mutation });
+                }
+            }
+            Self::Nothing => {
+                let roots = neighbours
+                    .iter()
+                    .filter(|tile| **tile == Some(Self::MutatedRoot))
+                    .count();
+                if roots > 0 && rng.gen_bool(chance(ROOT_SPREAD_RATE, roots) as f64) {
+                    events.push(GameEvent::RootSpread { pos });
+                    return Some(Self::MutatedRoot);
                 }
             }
             _ => {}
         }
         None
     }
-    fn handle_land(&mut self, player: &mut Player, assets: &Assets) -> Option<Option<Mutation>> {
+    fn handle_land(
+        &mut self,
+        pos: Vec2<f32>,
+        fertility: &mut f32,
+        player: &mut Player,
+        rng: &mut StdRng,
+        events: &mut Vec<GameEvent>,
+    ) {
         match self {
             Self::BrokenShell => {
-                assets.crack.play();
                 *self = Self::CrushedShell;
-                return Some(None);
+                events.push(GameEvent::ShellCrushed { pos });
             }
             Self::CrushedShell => {
+                *fertility = (*fertility + 0.5).min(MAX_FERTILITY);
                 *self = Self::FertilizedSoil {
-                    time: FERTILIZED_SOIL_TIME,
+                    time: CropSpecies::Quick.grow_time(),
                     mutation: None,
+                    crop: CropSpecies::Quick,
                 };
-                return Some(None);
+                events.push(GameEvent::SoilFertilized {
+                    pos,
+                    mutation: None,
+                });
             }
             Self::Poop { mutation } | Self::Food { mutation } => {
                 let mutation = *mutation;
+                let crop = if let Self::Poop { .. } = self {
+                    *fertility = (*fertility + 1.0).min(MAX_FERTILITY);
+                    CropSpecies::Hearty
+                } else {
+                    CropSpecies::Thorny
+                };
                 *self = Self::FertilizedSoil {
-                    time: FERTILIZED_SOIL_TIME,
+                    time: crop.grow_time(),
                     mutation,
+                    crop,
                 };
-                return Some(mutation);
+                events.push(GameEvent::SoilFertilized { pos, mutation });
             }
-            Self::FertilizedSoil { mutation, .. } => {
+            Self::FertilizedSoil {
+                time,
+                mutation,
+                crop,
+            } => {
                 let mutation = *mutation;
+                if crop.hurts(*time) {
+                    player.hurt(pos, mutation, events);
+                }
                 *self = Self::Nothing;
-                return Some(mutation);
+                events.push(GameEvent::SoilCleared { pos, mutation });
             }
             Self::MutatedRoot => {
                 *self = Self::Nothing;
-                if player.mutation != Some(Mutation::RGB) {
-                    loop {
-                        let new_mutation = global_rng().gen::<Mutation>().mix(player.mutation);
-                        if player.mutation != new_mutation {
-                            player.mutation = new_mutation;
-                            break;
-                        }
+                use rand::seq::SliceRandom;
+                let mut primaries = Mutation::primaries();
+                primaries.shuffle(rng);
+                if let Some(new_mutation) = primaries
+                    .into_iter()
+                    .map(|primary| primary.mix(player.mutation))
+                    .find(|&new_mutation| new_mutation != player.mutation)
+                {
+                    player.mutation = new_mutation;
+                    events.push(GameEvent::Mutated {
+                        pos: player.pos,
+                        mutation: player.mutation,
+                    });
+                }
+                events.push(GameEvent::RootDestroyed { pos });
+            }
+            Self::Bleach { mutation } => {
+                if let Some(current) = player.mutation {
+                    player.mutation = current.bleach(*mutation);
+                    if player.mutation != Some(current) {
+                        events.push(GameEvent::Bleached {
+                            pos: player.pos,
+                            mutation: player.mutation,
+                        });
                     }
-                    assets.mutate.play();
                 }
-                return Some(Some(Mutation::RGB));
             }
             Self::Trophey { mutation } => {
-                assets.trophey.play();
+                events.push(GameEvent::TropheyCollected {
+                    pos,
+                    mutation: *mutation,
+                });
                 player.tropheys.insert(*mutation);
                 *self = Self::Nothing;
             }
             _ => {}
         }
-        None
     }
-    fn collide_projectile(&mut self, p: &mut Projectile, assets: &Assets) {
+    fn collide_projectile(
+        &mut self,
+        pos: Vec2<f32>,
+        p: &mut Projectile,
+        events: &mut Vec<GameEvent>,
+    ) {
         match self {
-            Self::AngryWeed { mutation, .. } if *mutation == p.mutation => {
-                assets.weed_death.play();
-                if let Some(mutation) = *mutation {
-                    *self = Self::Trophey { mutation };
-                } else {
-                    *self = Self::MutatedRoot;
+            Self::AngryWeed {
+                mutation, species, ..
+            } if species.killed_by(*mutation, p.mutation) => {
+                events.push(GameEvent::WeedKilled {
+                    pos,
+                    mutation: *mutation,
+                });
+                match *mutation {
+                    Some(mutation) if Some(mutation) == p.mutation => {
+                        *self = Self::Trophey { mutation };
+                    }
+                    _ => *self = Self::MutatedRoot,
                 }
                 p.alive = false;
             }
+            Self::Wall | Self::Rock => {
+                p.alive = false;
+            }
             _ => {}
         }
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Map {
     pub tiles: Vec<Vec<Tile>>,
+    #[serde(default)]
+    pub fertility: Vec<Vec<f32>>,
     shared: SharedState,
+    // Tiles as they were at the start of the reaction pass, reused every frame
+    #[serde(skip)]
+    back: Vec<Vec<Tile>>,
 }
 
 impl Map {
@@ -217,34 +403,209 @@ impl Map {
     }
     pub fn new() -> Self {
         let size = vec2(16, 16);
-        Self {
-            tiles: vec![vec![Tile::Nothing; size.y]; size.x],
-            shared: SharedState { peace: 3 },
-        }
+        Self::from_tiles(vec![vec![Tile::Nothing; size.y]; size.x], 3)
+    }
+    pub fn from_tiles(tiles: Vec<Vec<Tile>>, peace: usize) -> Self {
+        let mut map = Self {
+            tiles,
+            fertility: Vec::new(),
+            shared: SharedState { peace },
+            back: Vec::new(),
+        };
+        map.resize(map.size());
+        map
     }
     pub fn size(&self) -> Vec2<usize> {
         vec2(self.tiles.len(), self.tiles[0].len())
     }
+    pub fn resize(&mut self, size: Vec2<usize>) {
+        self.tiles.resize(size.x, vec![Tile::Nothing; size.y]);
+        for row in &mut self.tiles {
+            row.resize(size.y, Tile::Nothing);
+        }
+        self.fertility.resize(size.x, vec![BASE_FERTILITY; size.y]);
+        for row in &mut self.fertility {
+            row.resize(size.y, BASE_FERTILITY);
+        }
+    }
+    pub fn peace(&self) -> usize {
+        self.shared.peace
+    }
+    pub fn is_solid(&self, pos: Vec2<i32>) -> bool {
+        if pos.x < 0 || pos.y < 0 {
+            return true;
+        }
+        match self
+            .tiles
+            .get(pos.x as usize)
+            .and_then(|row| row.get(pos.y as usize))
+        {
+            Some(tile) => tile.is_solid(),
+            None => true,
+        }
+    }
+    fn solid_cells_near(&self, pos: Vec2<f32>, radius: f32) -> Vec<Vec2<f32>> {
+        let mut cells = Vec::new();
+        for x in (pos.x - radius).floor() as i32..=(pos.x + radius).floor() as i32 {
+            for y in (pos.y - radius).floor() as i32..=(pos.y + radius).floor() as i32 {
+                if self.is_solid(vec2(x, y)) {
+                    cells.push(vec2(x as f32, y as f32));
+                }
+            }
+        }
+        cells
+    }
+    fn closest_in_cell(pos: Vec2<f32>, cell: Vec2<f32>) -> Vec2<f32> {
+        vec2(
+            clamp(pos.x, cell.x..=cell.x + 1.0),
+            clamp(pos.y, cell.y..=cell.y + 1.0),
+        )
+    }
+    pub fn push_out(&self, mut pos: Vec2<f32>, radius: f32) -> Vec2<f32> {
+        for cell in self.solid_cells_near(pos, radius) {
+            let v = pos - Self::closest_in_cell(pos, cell);
+            if v.len() > 1e-5 {
+                if v.len() < radius {
+                    pos += v.normalize() * (radius - v.len());
+                }
+            } else {
+                let exits = [
+                    (pos.x - cell.x, vec2(cell.x - radius, pos.y)),
+                    (cell.x + 1.0 - pos.x, vec2(cell.x + 1.0 + radius, pos.y)),
+                    (pos.y - cell.y, vec2(pos.x, cell.y - radius)),
+                    (cell.y + 1.0 - pos.y, vec2(pos.x, cell.y + 1.0 + radius)),
+                ];
+                pos = exits
+                    .iter()
+                    .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+                    .unwrap()
+                    .1;
+            }
+        }
+        pos
+    }
     pub fn land(
         &mut self,
         pos: Vec2<f32>,
-        particles: &mut Particles,
         player: &mut Player,
-        assets: &Assets,
+        rng: &mut StdRng,
+        events: &mut Vec<GameEvent>,
     ) {
         let pos = pos.map(|x| x as usize);
-        if let Some(mutation) = self.tiles[pos.x][pos.y].handle_land(player, assets) {
-            assets.smoke.play();
-            particles.boom(pos.map(|x| x as f32 + 0.5), mutation);
+        let tile = &mut self.tiles[pos.x][pos.y];
+        let was_unfertilized = tile.kind() != TileKind::FertilizedSoil;
+        let growth = player.has(Ability::Growth);
+        tile.handle_land(
+            pos.map(|x| x as f32 + 0.5),
+            &mut self.fertility[pos.x][pos.y],
+            player,
+            rng,
+            events,
+        );
+        if let Tile::FertilizedSoil { time, .. } = tile {
+            if was_unfertilized && growth {
+                *time *= GROWTH_FACTOR;
+            }
         }
     }
-    pub fn collide_projectile(&mut self, p: &mut Projectile, assets: &Assets) {
-        let pos = p.pos.map(|x| x as usize);
-        if pos != p.spawn {
-            self.tiles[pos.x][pos.y].collide_projectile(p, assets);
+    pub fn harvest(&mut self, pos: Vec2<f32>, mutation: Option<Mutation>, player: &Player) {
+        let pos = pos.map(|x| x as usize);
+        let fertility = &mut self.fertility[pos.x][pos.y];
+        *fertility = (*fertility - 1.0).max(0.0);
+        let mut time = CropSpecies::Thorny.grow_time();
+        if player.has(Ability::Growth) {
+            time *= GROWTH_FACTOR;
+        }
+        self.tiles[pos.x][pos.y] = Tile::FertilizedSoil {
+            time,
+            mutation,
+            crop: CropSpecies::Thorny,
+        };
+    }
+    pub fn collide_projectile(
+        &mut self,
+        from: Vec2<f32>,
+        p: &mut Projectile,
+        events: &mut Vec<GameEvent>,
+    ) {
+        let size = self.size().map(|x| x as i64);
+        let next = |pos: f32, cell: i64, delta: f32| {
+            if delta > 0.0 {
+                ((cell + 1) as f32 - pos) / delta
+            } else if delta < 0.0 {
+                (cell as f32 - pos) / delta
+            } else {
+                std::f32::INFINITY
+            }
+        };
+        let mut from = from;
+        let mut cell = from.map(|x| x.floor() as i64);
+        'segment: loop {
+            let delta = p.pos - from;
+            let end = p.pos.map(|x| x.floor() as i64);
+            let step = vec2(delta.x.signum() as i64, delta.y.signum() as i64);
+            let mut t_max = vec2(next(from.x, cell.x, delta.x), next(from.y, cell.y, delta.y));
+            let t_delta = delta.map(|x| 1.0 / x.abs());
+            let mut t = 0.0;
+            let mut steps = ((end.x - cell.x).abs(), (end.y - cell.y).abs());
+            loop {
+                if cell.x >= 0 && cell.y >= 0 && cell.x < size.x && cell.y < size.y {
+                    let tile = cell.map(|x| x as usize);
+                    if tile != p.spawn {
+                        self.tiles[tile.x][tile.y].collide_projectile(
+                            tile.map(|x| x as f32 + 0.5),
+                            p,
+                            events,
+                        );
+                    }
+                }
+                // Projectiles destroyed earlier in the frame still hit the tile they are in
+                if !p.alive {
+                    p.pos = from + delta * t;
+                    return;
+                }
+                let along_x = if steps.0 > 0 && (steps.1 == 0 || t_max.x < t_max.y) {
+                    true
+                } else if steps.1 > 0 {
+                    false
+                } else {
+                    return;
+                };
+                let mut next_cell = cell;
+                if along_x {
+                    next_cell.x += step.x;
+                    t = t_max.x;
+                } else {
+                    next_cell.y += step.y;
+                    t = t_max.y;
+                }
+                if self.bounces_off(next_cell) && p.take_bounce() {
+                    let hit = from + delta * t;
+                    if along_x {
+                        p.vel.x = -p.vel.x;
+                        p.pos.x = 2.0 * hit.x - p.pos.x;
+                    } else {
+                        p.vel.y = -p.vel.y;
+                        p.pos.y = 2.0 * hit.y - p.pos.y;
+                    }
+                    from = hit;
+                    continue 'segment;
+                }
+                cell = next_cell;
+                if along_x {
+                    t_max.x += t_delta.x;
+                    steps.0 -= 1;
+                } else {
+                    t_max.y += t_delta.y;
+                    steps.1 -= 1;
+                }
+            }
         }
     }
-    pub fn text_at(&self, pos: Vec2<f32>) -> Option<String> {
+    fn bounces_off(&self, cell: Vec2<i64>) -> bool {
+        self.is_solid(cell.map(|x| x as i32))
+    }
+    pub fn text_at(&self, pos: Vec2<f32>) -> Option<&'static str> {
         fn close(pos: f32, size: usize) -> bool {
             pos.abs() < 0.5 || (pos - size as f32).abs() < 0.5
         }
@@ -253,9 +614,20 @@ impl Map {
             .get(pos.x.max(0.0) as usize)
             .and_then(|row| row.get(pos.y.max(0.0) as usize))
         {
-            None | Some(Tile::Nothing) => {
+            None => {
                 if close(pos.x, self.size().x) || close(pos.y, self.size().y) {
-                    Some("Wall".to_owned())
+                    Some(Tile::Wall.text())
+                } else {
+                    None
+                }
+            }
+            Some(Tile::Nothing) => {
+                if self
+                    .solid_cells_near(pos, 0.5)
+                    .into_iter()
+                    .any(|cell| (pos - Self::closest_in_cell(pos, cell)).len() < 0.5)
+                {
+                    Some(Tile::Wall.text())
                 } else {
                     None
                 }
@@ -266,27 +638,99 @@ impl Map {
     pub fn update(
         &mut self,
         delta_time: f32,
-        particles: &mut Particles,
         projectiles: &mut Vec<Projectile>,
         player: &mut Player,
-        assets: &Assets,
+        rng: &mut StdRng,
+        events: &mut Vec<GameEvent>,
     ) {
+        let mut harvest = Vec::new();
         for (x, row) in self.tiles.iter_mut().enumerate() {
             for (y, tile) in row.iter_mut().enumerate() {
-                if let Some(mutation) = tile.update(
+                tile.update(
                     delta_time,
                     &mut self.shared,
                     vec2(x, y),
+                    self.fertility[x][y],
                     projectiles,
                     player,
-                    assets,
+                    rng,
+                    events,
+                    &mut harvest,
+                );
+            }
+        }
+        for (pos, mutation) in harvest {
+            let mut free = Vec::new();
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let x = pos.x as i32 + dx;
+                    let y = pos.y as i32 + dy;
+                    if x >= 0
+                        && x < self.size().x as _
+                        && y >= 0
+                        && y < self.size().y as _
+                        && self.tiles[x as usize][y as usize] == Tile::Nothing
+                    {
+                        free.push(vec2(x as usize, y as usize));
+                    }
+                }
+            }
+            use rand::seq::SliceRandom;
+            if let Some(&pos) = free.choose(rng) {
+                self.tiles[pos.x][pos.y] = Tile::Food { mutation };
+                events.push(GameEvent::FoodGrown {
+                    pos: pos.map(|x| x as f32 + 0.5),
+                    mutation,
+                });
+            }
+        }
+        self.back.clone_from(&self.tiles);
+        let tiles = &self.back;
+        for (x, row) in tiles.iter().enumerate() {
+            for (y, tile) in row.iter().enumerate() {
+                let neighbour = |dx: i32, dy: i32| {
+                    tiles
+                        .get((x as i32 + dx) as usize)
+                        .and_then(|row| row.get((y as i32 + dy) as usize))
+                        .copied()
+                };
+                let neighbours = [
+                    neighbour(-1, 0),
+                    neighbour(1, 0),
+                    neighbour(0, -1),
+                    neighbour(0, 1),
+                ];
+                if let Some(tile) = tile.react(
+                    vec2(x, y),
+                    &neighbours,
+                    delta_time,
+                    &self.shared,
+                    rng,
+                    events,
                 ) {
-                    assets.smoke.play();
-                    particles.boom(vec2(x as f32 + 0.5, y as f32 + 0.5), mutation);
+                    self.tiles[x][y] = tile;
                 }
             }
         }
     }
+    pub fn draw_fertility(
+        &self,
+        framebuffer: &mut ugli::Framebuffer,
+        camera: &Camera,
+        primitive: &Primitive,
+    ) {
+        for (x, row) in self.fertility.iter().enumerate() {
+            for (y, &fertility) in row.iter().enumerate() {
+                let t = fertility / MAX_FERTILITY;
+                primitive.quad(
+                    framebuffer,
+                    camera,
+                    AABB::pos_size(vec2(x as f32, y as f32), vec2(1.0, 1.0)),
+                    Color::rgba(t, 0.2, 1.0 - t, 0.35),
+                );
+            }
+        }
+    }
     pub fn draw(
         &self,
         framebuffer: &mut ugli::Framebuffer,
@@ -394,7 +838,11 @@ impl Map {
                             );
                         }
                     }
-                    Tile::FertilizedSoil { mutation, .. } => {
+                    Tile::FertilizedSoil {
+                        time,
+                        mutation,
+                        crop,
+                    } => {
                         for &dv in &[vec2(0.2, 0.5), vec2(0.3, 0.3), vec2(0.5, 0.2)] {
                             let pos = vec2(x as f32, y as f32) + dv;
                             primitive.line(
@@ -406,6 +854,14 @@ impl Map {
                                 mutation.map_or(Color::BLACK, |m| m.color()),
                             );
                         }
+                        crop.draw(
+                            framebuffer,
+                            camera,
+                            primitive,
+                            vec2(x as f32, y as f32),
+                            *time,
+                            *mutation,
+                        );
                     }
                     Tile::Food { mutation } => {
                         primitive.circle(
@@ -456,7 +912,77 @@ impl Map {
                             );
                         }
                     }
-                    Tile::AngryWeed { time, mutation } => {
+                    Tile::AngryWeed {
+                        time,
+                        mutation,
+                        species,
+                    } => {
+                        let center = vec2(x as f32 + 0.5, y as f32 + 0.5);
+                        match *species {
+                            WeedSpecies::Angry => {}
+                            WeedSpecies::Spreader => {
+                                for &dx in &[-0.25, 0.0, 0.25] {
+                                    primitive.line(
+                                        framebuffer,
+                                        camera,
+                                        center,
+                                        center + vec2(dx, 0.4),
+                                        0.08,
+                                        Color::BLACK,
+                                    );
+                                }
+                            }
+                            WeedSpecies::Sprinkler => {
+                                for i in 0..8 {
+                                    let dir = Vec2::rotated(
+                                        vec2(0.4, 0.0),
+                                        i as f32 * std::f32::consts::PI / 4.0,
+                                    );
+                                    primitive.line(
+                                        framebuffer,
+                                        camera,
+                                        center,
+                                        center + dir,
+                                        0.05,
+                                        Color::BLACK,
+                                    );
+                                }
+                            }
+                            WeedSpecies::Burst { shots } => {
+                                for i in 0..3 {
+                                    primitive.circle(
+                                        framebuffer,
+                                        camera,
+                                        center + vec2(-0.2 + i as f32 * 0.2, 0.4),
+                                        if i < shots { 0.08 } else { 0.05 },
+                                        Color::BLACK,
+                                    );
+                                }
+                            }
+                            WeedSpecies::Sniper { aim } => {
+                                let dir = aim
+                                    .filter(|aim| (*aim - center).len() > 1e-5)
+                                    .map_or(vec2(0.0, 1.0), |aim| (aim - center).normalize());
+                                primitive.line(
+                                    framebuffer,
+                                    camera,
+                                    center,
+                                    center + dir * 0.5,
+                                    0.1,
+                                    Color::BLACK,
+                                );
+                                if let Some(aim) = aim {
+                                    primitive.line(
+                                        framebuffer,
+                                        camera,
+                                        center,
+                                        aim,
+                                        0.03,
+                                        Color::rgba(1.0, 0.0, 0.0, 0.5),
+                                    );
+                                }
+                            }
+                        }
                         primitive.circle(
                             framebuffer,
                             camera,
@@ -487,7 +1013,7 @@ impl Map {
                             vec2(0.7, 0.5),
                         ];
                         for p in &mut ps {
-                            p.y = 0.4 + (p.y - 0.4) * (1.0 - *time / ANGRY_WEED_SHOOT_TIME);
+                            p.y = 0.4 + (p.y - 0.4) * (1.0 - *time / species.shoot_time());
                         }
                         for ps in ps.windows(2) {
                             primitive.line(
@@ -500,6 +1026,28 @@ impl Map {
                             );
                         }
                     }
+                    Tile::Bleach { mutation } => {
+                        let center = vec2(x as f32 + 0.5, y as f32 + 0.5);
+                        primitive.circle(framebuffer, camera, center, 0.4, Color::BLACK);
+                        primitive.circle(
+                            framebuffer,
+                            camera,
+                            center,
+                            0.3,
+                            Color::rgb(0.85, 0.95, 1.0),
+                        );
+                        if let Some(mutation) = mutation {
+                            primitive.circle(framebuffer, camera, center, 0.15, mutation.color());
+                        }
+                        primitive.line(
+                            framebuffer,
+                            camera,
+                            center - vec2(0.2, 0.2),
+                            center + vec2(0.2, 0.2),
+                            0.08,
+                            Color::BLACK,
+                        );
+                    }
                     Tile::MutatedRoot => {
                         primitive.line(
                             framebuffer,
@@ -534,6 +1082,37 @@ impl Map {
                             Color::BLUE,
                         );
                     }
+                    Tile::Wall => {
+                        primitive.quad(
+                            framebuffer,
+                            camera,
+                            AABB::pos_size(vec2(x as f32, y as f32), vec2(1.0, 1.0)),
+                            Color::BLACK,
+                        );
+                    }
+                    Tile::Rock => {
+                        let center = vec2(x as f32 + 0.5, y as f32 + 0.5);
+                        primitive.quad(
+                            framebuffer,
+                            camera,
+                            AABB::pos_size(vec2(x as f32, y as f32), vec2(1.0, 1.0)),
+                            Color::BLACK,
+                        );
+                        primitive.quad(
+                            framebuffer,
+                            camera,
+                            AABB::pos_size(vec2(x as f32 + 0.1, y as f32 + 0.1), vec2(0.8, 0.8)),
+                            Color::WHITE,
+                        );
+                        primitive.line(
+                            framebuffer,
+                            camera,
+                            center + vec2(-0.2, 0.1),
+                            center + vec2(0.1, 0.2),
+                            0.1,
+                            Color::BLACK,
+                        );
+                    }
                     Tile::Trophey { mutation } => {
                         let center = vec2(x as f32 + 0.5, y as f32 + 0.5);
                         primitive.circle(framebuffer, camera, center, 0.4, Color::BLACK);
@@ -560,3 +1139,136 @@ impl Map {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sweep(map: &mut Map, mut p: Projectile, delta_time: f32) -> Projectile {
+        let from = p.pos;
+        p.update(delta_time, vec2(0.0, 0.0));
+        map.collide_projectile(from, &mut p, &mut Vec::new());
+        p
+    }
+
+    #[test]
+    fn fast_projectiles_stop_at_the_first_wall() {
+        let mut tiles = vec![vec![Tile::Nothing; 10]; 10];
+        tiles[5][2] = Tile::Wall;
+        tiles[7][2] = Tile::Wall;
+        let mut map = Map::from_tiles(tiles, 0);
+        let p = sweep(
+            &mut map,
+            Projectile::new(vec2(1.5, 2.5), 0.2, vec2(80.0, 0.0), None),
+            0.1,
+        );
+        assert!(!p.alive);
+        assert!((p.pos.x - 5.0).abs() < 1e-4);
+        let p = sweep(
+            &mut map,
+            Projectile::new(vec2(1.5, 1.5), 0.2, vec2(3.0, 3.0), None),
+            0.5,
+        );
+        assert!(p.alive);
+    }
+
+    #[test]
+    fn bouncing_projectiles_reflect_off_solid_tiles() {
+        let mut tiles = vec![vec![Tile::Nothing; 10]; 10];
+        tiles[6][5] = Tile::Rock;
+        let mut map = Map::from_tiles(tiles, 0);
+        let p = sweep(
+            &mut map,
+            Projectile::new(vec2(5.5, 5.5), 0.2, vec2(2.0, 0.0), None)
+                .with_behaviour(Behaviour::Bouncing { bounces: 1 }),
+            0.5,
+        );
+        assert!(p.alive);
+        assert!((p.pos.x - 5.5).abs() < 1e-4);
+        assert!(p.vel.x < 0.0);
+        assert_eq!(p.behaviour, Behaviour::Bouncing { bounces: 0 });
+        let p = sweep(
+            &mut map,
+            Projectile::new(vec2(9.5, 5.5), 0.2, vec2(2.0, 0.0), None)
+                .with_behaviour(Behaviour::Bouncing { bounces: 1 }),
+            0.5,
+        );
+        assert!((p.pos.x - 9.5).abs() < 1e-4);
+        let p = sweep(
+            &mut map,
+            Projectile::new(vec2(5.5, 5.5), 0.2, vec2(2.0, 0.0), None),
+            0.5,
+        );
+        assert!(!p.alive);
+        assert_eq!(map.tiles[6][5], Tile::Rock);
+    }
+
+    #[test]
+    fn reactions_read_the_tiles_from_before_the_pass() {
+        let red = Mutation::from_name("Red").unwrap();
+        let blue = Mutation::from_name("Blue").unwrap();
+        let mut tiles = vec![vec![Tile::Nothing; 3]; 4];
+        tiles[0][0] = Tile::MutatedRoot;
+        tiles[0][2] = Tile::Food {
+            mutation: Some(red),
+        };
+        tiles[1][2] = Tile::Food {
+            mutation: Some(blue),
+        };
+        let mut map = Map::from_tiles(tiles, 0);
+        let mut player = Player::new(vec2(3.5, 2.5));
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut events = Vec::new();
+        map.update(1000.0, &mut Vec::new(), &mut player, &mut rng, &mut events);
+        assert_eq!(map.tiles[1][0], Tile::MutatedRoot);
+        assert_eq!(map.tiles[0][1], Tile::MutatedRoot);
+        assert_eq!(map.tiles[2][0], Tile::Nothing);
+        assert_eq!(map.tiles[1][1], Tile::Nothing);
+        let purple = Some(red.mix(Some(blue)).unwrap());
+        assert_eq!(map.tiles[0][2], Tile::Food { mutation: purple });
+        assert_eq!(map.tiles[1][2], Tile::Food { mutation: purple });
+    }
+
+    #[test]
+    fn weeds_sprout_on_neighbouring_soil() {
+        let mut tiles = vec![vec![Tile::Nothing; 1]; 3];
+        tiles[0][0] = Tile::AngryWeed {
+            time: 100.0,
+            mutation: None,
+            species: WeedSpecies::Angry,
+        };
+        tiles[1][0] = Tile::FertilizedSoil {
+            time: 100.0,
+            mutation: None,
+            crop: CropSpecies::Quick,
+        };
+        let mut map = Map::from_tiles(tiles, 0);
+        let mut player = Player::new(vec2(2.5, 0.5));
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut events = Vec::new();
+        map.update(100.0, &mut Vec::new(), &mut player, &mut rng, &mut events);
+        assert_eq!(map.tiles[1][0].kind(), TileKind::AngryWeed);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, GameEvent::WeedSprouted { .. })));
+    }
+
+    #[test]
+    fn destroyed_projectiles_still_hit_their_tile() {
+        let weed = Tile::AngryWeed {
+            time: ANGRY_WEED_SHOOT_TIME,
+            mutation: None,
+            species: WeedSpecies::Angry,
+        };
+        let mut tiles = vec![vec![Tile::Nothing; 4]; 4];
+        tiles[2][2] = weed;
+        let mut map = Map::from_tiles(tiles, 0);
+        let mut p = Projectile::new(vec2(0.5, 0.5), 0.2, vec2(0.0, 0.0), None);
+        p.pos = vec2(2.5, 2.5);
+        p.alive = false;
+        let mut events = Vec::new();
+        map.collide_projectile(p.pos, &mut p, &mut events);
+        assert!(matches!(events[..], [GameEvent::WeedKilled { .. }]));
+        assert_eq!(map.tiles[2][2], Tile::MutatedRoot);
+    }
+}
@@ -0,0 +1,121 @@
+use super::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MenuAction {
+    Activate(usize),
+    Adjust(usize, f64),
+    Back,
+}
+
+pub struct Menu {
+    pub selected: usize,
+    hovered: Option<usize>,
+    mouse_pos: Option<Vec2<f32>>,
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            hovered: None,
+            mouse_pos: None,
+        }
+    }
+    fn spacing(camera: &Camera, count: usize) -> f32 {
+        camera.fov * (0.55 / count as f32).min(0.1)
+    }
+    fn item_pos(camera: &Camera, spacing: f32, index: usize) -> Vec2<f32> {
+        camera.center + vec2(0.0, camera.fov * 0.1 - spacing * index as f32)
+    }
+    pub fn draw(
+        &mut self,
+        framebuffer: &mut ugli::Framebuffer,
+        camera: &Camera,
+        primitive: &Primitive,
+        mouse_pos: Vec2<f32>,
+        title: &str,
+        items: &[String],
+    ) {
+        let spacing = Self::spacing(camera, items.len());
+        let size = (camera.fov / 25.0).min(spacing / 2.0);
+        let mouse_pos = camera.screen_to_world(framebuffer, mouse_pos);
+        self.hovered = (0..items.len()).find(|&i| {
+            let pos = Self::item_pos(camera, spacing, i);
+            let half_width = (primitive.text_width(&items[i], size) + size) / 2.0;
+            (mouse_pos.x - pos.x).abs() < half_width
+                && (mouse_pos.y - pos.y - size * 1.5).abs() < size
+        });
+        if self.mouse_pos != Some(mouse_pos) {
+            if self.mouse_pos.is_some() {
+                if let Some(hovered) = self.hovered {
+                    self.selected = hovered;
+                }
+            }
+            self.mouse_pos = Some(mouse_pos);
+        }
+        self.selected = min(self.selected, items.len().saturating_sub(1));
+        primitive.quad(
+            framebuffer,
+            camera,
+            AABB::pos_size(
+                camera.center - vec2(camera.fov * 2.0, camera.fov),
+                vec2(camera.fov * 4.0, camera.fov * 2.0),
+            ),
+            Color::rgba(1.0, 1.0, 1.0, 0.7),
+        );
+        primitive.text_bubble(
+            framebuffer,
+            camera,
+            title.to_owned(),
+            camera.center + vec2(0.0, camera.fov * 0.25),
+            camera.fov / 25.0 * 1.5,
+        );
+        for (i, item) in items.iter().enumerate() {
+            let text = if i == self.selected {
+                format!("> {} <", item)
+            } else {
+                item.clone()
+            };
+            primitive.text_bubble(
+                framebuffer,
+                camera,
+                text,
+                Self::item_pos(camera, spacing, i),
+                size,
+            );
+        }
+    }
+    pub fn handle_event(
+        &mut self,
+        event: &geng::Event,
+        count: usize,
+        controls: &Controls,
+    ) -> Option<MenuAction> {
+        match *event {
+            geng::Event::KeyDown { key } => match (key, controls.action(key)) {
+                (geng::Key::Enter, _) => Some(MenuAction::Activate(self.selected)),
+                (geng::Key::Escape, _) | (geng::Key::Backspace, _) => Some(MenuAction::Back),
+                (_, Some(Action::MoveUp)) => {
+                    self.selected = (self.selected + count - 1) % count;
+                    None
+                }
+                (_, Some(Action::MoveDown)) => {
+                    self.selected = (self.selected + 1) % count;
+                    None
+                }
+                (_, Some(Action::MoveLeft)) => Some(MenuAction::Adjust(self.selected, -1.0)),
+                (_, Some(Action::MoveRight)) => Some(MenuAction::Adjust(self.selected, 1.0)),
+                (_, Some(Action::Jump)) => Some(MenuAction::Activate(self.selected)),
+                (_, Some(Action::Pause)) => Some(MenuAction::Back),
+                _ => None,
+            },
+            geng::Event::MouseDown { button, .. } => match (button, self.hovered) {
+                (geng::MouseButton::Left, Some(index)) => Some(MenuAction::Activate(index)),
+                (geng::MouseButton::Right, Some(index)) => Some(MenuAction::Adjust(index, -1.0)),
+                (geng::MouseButton::Right, None) => Some(MenuAction::Back),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
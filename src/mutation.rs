@@ -1,59 +1,91 @@
 use super::*;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-pub enum Mutation {
-    Red,
-    Green,
-    Blue,
-    Yellow,
-    Cyan,
-    Purple,
-    RGB,
+pub struct Mutation {
+    pub channels: u32,
+}
+
+impl Serialize for Mutation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Mutation {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Self::from_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("Unknown mutation {:?}", name)))
+    }
 }
 
 impl Distribution<Mutation> for distributions::Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Mutation {
-        match rng.gen_range(0..3) {
-            0 => Mutation::Red,
-            1 => Mutation::Green,
-            2 => Mutation::Blue,
-            _ => unreachable!(),
+        Mutation {
+            channels: 1 << rng.gen_range(0..Palette::current().channel_count()),
         }
     }
 }
 
 impl Mutation {
-    pub fn color(&self) -> Color<f32> {
-        match self {
-            Self::Red => Color::RED,
-            Self::Green => Color::GREEN,
-            Self::Blue => Color::BLUE,
-            Self::Cyan => Color::rgb(0.0, 1.0, 1.0),
-            Self::Yellow => Color::rgb(1.0, 1.0, 0.0),
-            Self::Purple => Color::rgb(1.0, 0.0, 1.0),
-            Self::RGB => global_rng().gen::<Self>().color(),
+    pub fn from_channels(channels: u32) -> Option<Self> {
+        let channels = channels & Palette::current().full();
+        if channels == 0 {
+            None
+        } else {
+            Some(Self { channels })
         }
     }
-    pub fn mix(self, other: Option<Self>) -> Option<Self> {
-        if self == Self::RGB || other == Some(Self::RGB) {
-            return Some(Self::RGB);
+    pub fn from_name(name: &str) -> Option<Self> {
+        Palette::current()
+            .find(name)
+            .and_then(|channels| Self::from_channels(channels))
+    }
+    pub fn all() -> Vec<Self> {
+        Palette::current()
+            .named()
+            .map(|channels| Self { channels })
+            .collect()
+    }
+    pub fn primaries() -> Vec<Self> {
+        (0..Palette::current().channel_count())
+            .map(|index| Self {
+                channels: 1 << index,
+            })
+            .collect()
+    }
+    pub fn full() -> Self {
+        Self {
+            channels: Palette::current().full(),
         }
-        let mut color = self.color();
-        if let Some(other) = other {
-            let other = other.color();
-            color.r = color.r.max(other.r);
-            color.g = color.g.max(other.g);
-            color.b = color.b.max(other.b);
+    }
+    pub fn is_full(&self) -> bool {
+        self.channels == Palette::current().full()
+    }
+    pub fn name(&self) -> String {
+        Palette::current().name(self.channels)
+    }
+    pub fn text(&self) -> String {
+        Palette::current().text(self.channels)
+    }
+    pub fn color(&self) -> Color<f32> {
+        Palette::current().color(self.channels)
+    }
+    pub fn abilities(&self) -> Vec<Ability> {
+        Palette::current().abilities(self.channels)
+    }
+    pub fn mix(self, other: Option<Self>) -> Option<Self> {
+        match other {
+            Some(other) => {
+                Self::from_channels(Palette::current().mix(self.channels, other.channels))
+            }
+            None => Some(self),
         }
-        match (color.r > 0.5, color.g > 0.5, color.b > 0.5) {
-            (false, false, false) => None,
-            (true, true, true) => Some(Self::RGB),
-            (true, false, false) => Some(Self::Red),
-            (false, true, false) => Some(Self::Green),
-            (false, false, true) => Some(Self::Blue),
-            (true, true, false) => Some(Self::Yellow),
-            (true, false, true) => Some(Self::Purple),
-            (false, true, true) => Some(Self::Cyan),
+    }
+    pub fn bleach(self, other: Option<Self>) -> Option<Self> {
+        match other {
+            Some(other) => Self::from_channels(self.channels & !other.channels),
+            None => None,
         }
     }
 }
@@ -0,0 +1,283 @@
+use super::*;
+
+const VOLUME_STEP: f64 = 0.1;
+const TEXT_SCALE_STEP: f32 = 0.25;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Page {
+    Main,
+    Audio,
+    Controls,
+    Display,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Row {
+    Page(Page),
+    MasterVolume,
+    SfxVolume,
+    UiVolume,
+    Mute,
+    Binding(Action),
+    Pointer,
+    ResetControls,
+    TextScale,
+    Particles,
+    Fertility,
+    Timer,
+    Back,
+}
+
+impl Page {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Main => "options.title",
+            Self::Audio => "options.audio",
+            Self::Controls => "options.controls",
+            Self::Display => "options.display",
+        }
+    }
+    fn rows(&self) -> &'static [Row] {
+        match self {
+            Self::Main => &[
+                Row::Page(Page::Audio),
+                Row::Page(Page::Controls),
+                Row::Page(Page::Display),
+                Row::Back,
+            ],
+            Self::Audio => &[
+                Row::MasterVolume,
+                Row::SfxVolume,
+                Row::UiVolume,
+                Row::Mute,
+                Row::Back,
+            ],
+            Self::Controls => &[
+                Row::Binding(Action::MoveUp),
+                Row::Binding(Action::MoveDown),
+                Row::Binding(Action::MoveLeft),
+                Row::Binding(Action::MoveRight),
+                Row::Binding(Action::Jump),
+                Row::Binding(Action::Restart),
+                Row::Binding(Action::Pause),
+                Row::Pointer,
+                Row::ResetControls,
+                Row::Back,
+            ],
+            Self::Display => &[
+                Row::TextScale,
+                Row::Particles,
+                Row::Fertility,
+                Row::Timer,
+                Row::Back,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OptionsEvent {
+    Changed,
+    Closed,
+}
+
+pub struct OptionsScreen {
+    menu: Menu,
+    page: Page,
+    rebinding: Option<Action>,
+}
+
+impl OptionsScreen {
+    pub fn new() -> Self {
+        Self {
+            menu: Menu::new(),
+            page: Page::Main,
+            rebinding: None,
+        }
+    }
+    fn row_text(&self, row: Row, settings: &Settings, locale: &Locale) -> String {
+        let audio = &settings.audio;
+        let display = &settings.display;
+        let percent =
+            |key: &str, value: f64| format!("{}: {}%", locale.get(key), (value * 100.0).round());
+        let toggle = |key: &str, value: bool| {
+            format!(
+                "{}: {}",
+                locale.get(key),
+                locale.get(if value { "options.on" } else { "options.off" })
+            )
+        };
+        match row {
+            Row::Page(page) => locale.get(page.title()).to_owned(),
+            Row::MasterVolume => percent("options.master_volume", audio.master_volume),
+            Row::SfxVolume => percent("options.sfx_volume", audio.sfx_volume),
+            Row::UiVolume => percent("options.ui_volume", audio.ui_volume),
+            Row::Mute => toggle("options.mute", audio.muted),
+            Row::Binding(action) => format!(
+                "{}: {}",
+                locale.get(action.text()),
+                if self.rebinding == Some(action) {
+                    locale.get("options.press_key").to_owned()
+                } else {
+                    settings.controls.keys_text(action)
+                }
+            ),
+            Row::Pointer => toggle("options.pointer", settings.controls.pointer),
+            Row::ResetControls => locale.get("options.reset_controls").to_owned(),
+            Row::TextScale => percent("options.text_scale", display.text_scale as f64),
+            Row::Particles => toggle("options.particles", display.particles),
+            Row::Fertility => toggle("options.fertility", display.fertility),
+            Row::Timer => toggle("options.timer", display.timer),
+            Row::Back => locale.get("options.back").to_owned(),
+        }
+    }
+    fn adjust(
+        row: Row,
+        settings: &mut Settings,
+        direction: f64,
+        wrap: bool,
+    ) -> Option<OptionsEvent> {
+        let audio = &mut settings.audio;
+        let display = &mut settings.display;
+        let volume = match row {
+            Row::MasterVolume => &mut audio.master_volume,
+            Row::SfxVolume => &mut audio.sfx_volume,
+            Row::UiVolume => &mut audio.ui_volume,
+            Row::Mute => {
+                audio.muted = !audio.muted;
+                return Some(OptionsEvent::Changed);
+            }
+            Row::TextScale => {
+                let scale = display.text_scale + direction as f32 * TEXT_SCALE_STEP;
+                display.text_scale = if wrap && scale > 2.0 {
+                    0.5
+                } else {
+                    clamp(scale, 0.5..=2.0)
+                };
+                return Some(OptionsEvent::Changed);
+            }
+            Row::Particles => {
+                display.particles = !display.particles;
+                return Some(OptionsEvent::Changed);
+            }
+            Row::Fertility => {
+                display.fertility = !display.fertility;
+                return Some(OptionsEvent::Changed);
+            }
+            Row::Timer => {
+                display.timer = !display.timer;
+                return Some(OptionsEvent::Changed);
+            }
+            Row::Pointer => {
+                settings.controls.pointer = !settings.controls.pointer;
+                return Some(OptionsEvent::Changed);
+            }
+            Row::ResetControls => {
+                settings.controls = Controls::default();
+                return Some(OptionsEvent::Changed);
+            }
+            Row::Page(_) | Row::Binding(_) | Row::Back => return None,
+        };
+        let value = ((*volume + direction * VOLUME_STEP) / VOLUME_STEP).round() * VOLUME_STEP;
+        *volume = if wrap && value > 1.0 {
+            0.0
+        } else {
+            clamp(value, 0.0..=1.0)
+        };
+        Some(OptionsEvent::Changed)
+    }
+    fn open(&mut self, page: Page) {
+        self.menu.selected = match page {
+            Page::Main => Page::Main
+                .rows()
+                .iter()
+                .position(|&row| row == Row::Page(self.page))
+                .unwrap_or(0),
+            _ => 0,
+        };
+        self.page = page;
+    }
+    fn back(&mut self) -> Option<OptionsEvent> {
+        if self.page == Page::Main {
+            return Some(OptionsEvent::Closed);
+        }
+        self.open(Page::Main);
+        None
+    }
+    pub fn handle_event(
+        &mut self,
+        event: &geng::Event,
+        settings: &mut Settings,
+    ) -> Option<OptionsEvent> {
+        if let Some(action) = self.rebinding {
+            return match *event {
+                geng::Event::KeyDown { key } => {
+                    if key == geng::Key::Escape {
+                        self.rebinding = None;
+                        return None;
+                    }
+                    if !settings.controls.bind(action, key) {
+                        return None;
+                    }
+                    self.rebinding = None;
+                    Some(OptionsEvent::Changed)
+                }
+                geng::Event::MouseDown { .. } => {
+                    self.rebinding = None;
+                    None
+                }
+                _ => None,
+            };
+        }
+        if let geng::Event::KeyDown { key: geng::Key::M } = event {
+            settings.audio.muted = !settings.audio.muted;
+            return Some(OptionsEvent::Changed);
+        }
+        let rows = self.page.rows();
+        match self
+            .menu
+            .handle_event(event, rows.len(), &settings.controls)?
+        {
+            MenuAction::Activate(index) => match rows[index] {
+                Row::Page(page) => {
+                    self.open(page);
+                    None
+                }
+                Row::Binding(action) => {
+                    self.rebinding = Some(action);
+                    None
+                }
+                Row::Back => self.back(),
+                row => Self::adjust(row, settings, 1.0, true),
+            },
+            MenuAction::Adjust(index, direction) => {
+                Self::adjust(rows[index], settings, direction, false)
+            }
+            MenuAction::Back => self.back(),
+        }
+    }
+    pub fn draw(
+        &mut self,
+        framebuffer: &mut ugli::Framebuffer,
+        camera: &Camera,
+        primitive: &Primitive,
+        mouse_pos: Vec2<f32>,
+        locale: &Locale,
+        settings: &Settings,
+    ) {
+        let items: Vec<String> = self
+            .page
+            .rows()
+            .iter()
+            .map(|&row| self.row_text(row, settings, locale))
+            .collect();
+        self.menu.draw(
+            framebuffer,
+            camera,
+            primitive,
+            mouse_pos,
+            locale.get(self.page.title()),
+            &items,
+        );
+    }
+}
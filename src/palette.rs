@@ -0,0 +1,276 @@
+use super::*;
+
+#[derive(Debug, Clone, Deserialize)]
+struct ChannelSource {
+    name: String,
+    color: [f32; 3],
+    #[serde(default)]
+    ability: Option<Ability>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ColorSource {
+    name: String,
+    channels: Vec<String>,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MixSource {
+    from: [String; 2],
+    into: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PaletteSource {
+    channels: Vec<ChannelSource>,
+    colors: Vec<ColorSource>,
+    #[serde(default)]
+    mix: Vec<MixSource>,
+}
+
+#[derive(Debug, Clone)]
+struct NamedColor {
+    name: String,
+    channels: u32,
+    text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Palette {
+    source: String,
+    channel_names: Vec<String>,
+    channel_colors: Vec<Color<f32>>,
+    channel_abilities: Vec<Option<Ability>>,
+    colors: Vec<NamedColor>,
+    mix: Vec<(u32, u32, u32)>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<Palette>>> = RefCell::new(None);
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::parse(include_str!("../palettes/default.json")).unwrap()
+    }
+}
+
+impl Palette {
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read palette {}: {}", path.display(), e))?;
+        Self::parse(&source).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+    pub fn parse(text: &str) -> Result<Self, serde_json::Error> {
+        use serde::de::Error;
+        let source: PaletteSource = serde_json::from_str(text)?;
+        if source.channels.is_empty() || source.channels.len() > 32 {
+            return Err(Error::custom("Palette needs 1 to 32 channels"));
+        }
+        let mut palette = Self {
+            source: text.to_owned(),
+            channel_names: source.channels.iter().map(|c| c.name.clone()).collect(),
+            channel_colors: source
+                .channels
+                .iter()
+                .map(|c| Color::rgb(c.color[0], c.color[1], c.color[2]))
+                .collect(),
+            channel_abilities: source.channels.iter().map(|c| c.ability).collect(),
+            colors: Vec::new(),
+            mix: Vec::new(),
+        };
+        for color in source.colors {
+            let mut channels = 0;
+            for name in &color.channels {
+                channels |= palette
+                    .channel(name)
+                    .ok_or_else(|| Error::custom(format!("Unknown channel {:?}", name)))?;
+            }
+            if channels == 0 {
+                return Err(Error::custom(format!("{:?} has no channels", color.name)));
+            }
+            let name = color.name;
+            palette.colors.push(NamedColor {
+                text: color.text.unwrap_or_else(|| name.clone()),
+                name,
+                channels,
+            });
+        }
+        for rule in source.mix {
+            let find = |name: &str| {
+                palette
+                    .find(name)
+                    .ok_or_else(|| Error::custom(format!("Unknown color {:?}", name)))
+            };
+            palette.mix.push((
+                find(&rule.from[0])?,
+                find(&rule.from[1])?,
+                find(&rule.into)?,
+            ));
+        }
+        Ok(palette)
+    }
+    pub fn install(self) {
+        CURRENT.with(|current| *current.borrow_mut() = Some(Rc::new(self)));
+    }
+    pub fn current() -> Rc<Self> {
+        CURRENT.with(|current| {
+            current
+                .borrow_mut()
+                .get_or_insert_with(|| Rc::new(Self::default()))
+                .clone()
+        })
+    }
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    fn channel(&self, name: &str) -> Option<u32> {
+        self.channel_names
+            .iter()
+            .position(|other| other == name)
+            .map(|index| 1 << index)
+    }
+    pub fn channel_count(&self) -> usize {
+        self.channel_names.len()
+    }
+    pub fn full(&self) -> u32 {
+        (!0u32) >> (32 - self.channel_count())
+    }
+    pub fn named(&self) -> impl Iterator<Item = u32> + '_ {
+        self.colors.iter().map(|color| color.channels)
+    }
+    pub fn find(&self, name: &str) -> Option<u32> {
+        if let Some(color) = self.colors.iter().find(|color| color.name == name) {
+            return Some(color.channels);
+        }
+        let mut channels = 0;
+        for name in name.split('+') {
+            channels |= self.channel(name)?;
+        }
+        Some(channels)
+    }
+    pub fn name(&self, channels: u32) -> String {
+        match self.colors.iter().find(|color| color.channels == channels) {
+            Some(color) => color.name.clone(),
+            None => self
+                .channel_names
+                .iter()
+                .enumerate()
+                .filter(|&(index, _)| channels & 1 << index != 0)
+                .map(|(_, name)| name.as_str())
+                .collect::<Vec<_>>()
+                .join("+"),
+        }
+    }
+    pub fn text(&self, channels: u32) -> String {
+        match self.colors.iter().find(|color| color.channels == channels) {
+            Some(color) => color.text.clone(),
+            None => self.name(channels),
+        }
+    }
+    pub fn color(&self, channels: u32) -> Color<f32> {
+        let colors: Vec<Color<f32>> = self
+            .channel_colors
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| channels & 1 << index != 0)
+            .map(|(_, &color)| color)
+            .collect();
+        if channels == self.full() && colors.len() > 1 {
+            return colors[global_rng().gen_range(0..colors.len())];
+        }
+        let mut result: Color<f32> = Color::rgb(0.0, 0.0, 0.0);
+        for color in colors {
+            result.r = result.r.max(color.r);
+            result.g = result.g.max(color.g);
+            result.b = result.b.max(color.b);
+        }
+        result
+    }
+    pub fn abilities(&self, channels: u32) -> Vec<Ability> {
+        let mut result = Vec::new();
+        for (index, ability) in self.channel_abilities.iter().enumerate() {
+            if let Some(ability) = *ability {
+                if channels & 1 << index != 0 && !result.contains(&ability) {
+                    result.push(ability);
+                }
+            }
+        }
+        result
+    }
+    pub fn mix(&self, a: u32, b: u32) -> u32 {
+        self.mix
+            .iter()
+            .find(|&&(x, y, _)| (x, y) == (a, b) || (x, y) == (b, a))
+            .map_or(a | b, |&(_, _, into)| into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUSTOM: &str = r#"{
+        "channels": [
+            {"name": "R", "color": [1, 0, 0]},
+            {"name": "B", "color": [0, 0, 1]},
+            {"name": "U", "color": [0.5, 0, 1]}
+        ],
+        "colors": [
+            {"name": "Red", "channels": ["R"]},
+            {"name": "Blue", "channels": ["B"]},
+            {"name": "Violet", "channels": ["U"]}
+        ],
+        "mix": [{"from": ["Red", "Violet"], "into": "Blue"}]
+    }"#;
+
+    #[test]
+    fn default_mix_and_bleach() {
+        let red = Mutation::from_name("Red").unwrap();
+        let blue = Mutation::from_name("Blue").unwrap();
+        let purple = red.mix(Some(blue)).unwrap();
+        assert_eq!(purple.name(), "Purple");
+        assert_eq!(purple.mix(Some(red)), Some(purple));
+        assert_eq!(red.mix(None), Some(red));
+        assert_eq!(purple.bleach(Some(red)), Some(blue));
+        assert_eq!(purple.bleach(Some(purple)), None);
+        assert_eq!(purple.bleach(None), None);
+        assert_eq!(serde_json::to_string(&purple).unwrap(), "\"Purple\"");
+        assert_eq!(
+            serde_json::from_str::<Mutation>("\"Red+Blue\"").unwrap(),
+            purple
+        );
+    }
+
+    #[test]
+    fn custom_palette() {
+        Palette::parse(CUSTOM).unwrap().install();
+        assert_eq!(Palette::current().source(), CUSTOM);
+        let red = Mutation::from_name("Red").unwrap();
+        let violet = Mutation::from_name("Violet").unwrap();
+        assert_eq!(red.mix(Some(violet)).unwrap().name(), "Blue");
+        assert_eq!(violet.mix(Some(red)).unwrap().name(), "Blue");
+        assert_eq!(Mutation::all().len(), 3);
+        assert_eq!(Mutation::primaries().len(), 3);
+        assert!(!Mutation::from_name("R+B").unwrap().is_full());
+        assert!(Mutation::from_name("Yellow").is_none());
+    }
+
+    #[test]
+    fn invalid_palettes() {
+        assert!(Palette::parse(r#"{"channels": [], "colors": []}"#).is_err());
+        let unknown_channel = r#"{
+            "channels": [{"name": "R", "color": [1, 0, 0]}],
+            "colors": [{"name": "Red", "channels": ["G"]}]
+        }"#;
+        assert!(Palette::parse(unknown_channel).is_err());
+        let unknown_color = r#"{
+            "channels": [{"name": "R", "color": [1, 0, 0]}],
+            "colors": [{"name": "Red", "channels": ["R"]}],
+            "mix": [{"from": ["Red", "Red"], "into": "Green"}]
+        }"#;
+        assert!(Palette::parse(unknown_color).is_err());
+    }
+}
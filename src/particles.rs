@@ -47,6 +47,27 @@ impl Particles {
             self.spawn(pos, 0.4, mutation);
         }
     }
+    pub fn handle_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::ShellCracked { pos }
+            | GameEvent::ShellDropped { pos }
+            | GameEvent::ShellCrushed { pos } => self.boom(pos, None),
+            GameEvent::RootDestroyed { pos } | GameEvent::RootSpread { pos } => {
+                self.boom(pos, Some(Mutation::full()))
+            }
+            GameEvent::SoilFertilized { pos, mutation }
+            | GameEvent::SoilCleared { pos, mutation }
+            | GameEvent::FoodGrown { pos, mutation }
+            | GameEvent::FoodPollinated { pos, mutation }
+            | GameEvent::WeedSprouted { pos, mutation }
+            | GameEvent::Pooped { pos, mutation }
+            | GameEvent::Bleached { pos, mutation }
+            | GameEvent::ProjectileDestroyed { pos, mutation }
+            | GameEvent::PlayerHit { pos, mutation }
+            | GameEvent::PlayerDied { pos, mutation } => self.boom(pos, mutation),
+            _ => {}
+        }
+    }
     pub fn update(&mut self, delta_time: f32) {
         for particle in &mut self.particles {
             particle.update(delta_time);
@@ -0,0 +1,134 @@
+use super::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Item {
+    Resume,
+    Restart,
+    Options,
+    Gallery,
+    #[cfg(not(target_arch = "wasm32"))]
+    Quit,
+}
+
+impl Item {
+    fn text(&self) -> &'static str {
+        match self {
+            Self::Resume => "pause.resume",
+            Self::Restart => "pause.restart",
+            Self::Options => "pause.options",
+            Self::Gallery => "pause.gallery",
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Quit => "pause.quit",
+        }
+    }
+}
+
+const ITEMS: &[Item] = &[
+    Item::Resume,
+    Item::Restart,
+    Item::Options,
+    Item::Gallery,
+    #[cfg(not(target_arch = "wasm32"))]
+    Item::Quit,
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PauseAction {
+    Resume,
+    Restart,
+    SettingsChanged,
+    #[cfg(not(target_arch = "wasm32"))]
+    Quit,
+}
+
+pub struct PauseMenu {
+    menu: Menu,
+    options: Option<OptionsScreen>,
+    gallery: Option<Gallery>,
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        Self {
+            menu: Menu::new(),
+            options: None,
+            gallery: None,
+        }
+    }
+    pub fn at_root(&self) -> bool {
+        self.options.is_none() && self.gallery.is_none()
+    }
+    pub fn handle_event(
+        &mut self,
+        event: &geng::Event,
+        settings: &mut Settings,
+    ) -> Option<PauseAction> {
+        if let Some(options) = &mut self.options {
+            return match options.handle_event(event, settings)? {
+                OptionsEvent::Changed => Some(PauseAction::SettingsChanged),
+                OptionsEvent::Closed => {
+                    self.options = None;
+                    None
+                }
+            };
+        }
+        if let Some(gallery) = &mut self.gallery {
+            if gallery.handle_event(event, &settings.controls) {
+                self.gallery = None;
+            }
+            return None;
+        }
+        match self
+            .menu
+            .handle_event(event, ITEMS.len(), &settings.controls)?
+        {
+            MenuAction::Activate(index) => match ITEMS[index] {
+                Item::Resume => Some(PauseAction::Resume),
+                Item::Restart => Some(PauseAction::Restart),
+                Item::Options => {
+                    self.options = Some(OptionsScreen::new());
+                    None
+                }
+                Item::Gallery => {
+                    self.gallery = Some(Gallery::new());
+                    None
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                Item::Quit => Some(PauseAction::Quit),
+            },
+            MenuAction::Adjust(..) => None,
+            MenuAction::Back => Some(PauseAction::Resume),
+        }
+    }
+    pub fn draw(
+        &mut self,
+        framebuffer: &mut ugli::Framebuffer,
+        camera: &Camera,
+        primitive: &Primitive,
+        mouse_pos: Vec2<f32>,
+        locale: &Locale,
+        settings: &Settings,
+        profile: &Profile,
+    ) {
+        if let Some(options) = &mut self.options {
+            options.draw(framebuffer, camera, primitive, mouse_pos, locale, settings);
+            return;
+        }
+        if let Some(gallery) = &mut self.gallery {
+            gallery.draw(framebuffer, camera, primitive, mouse_pos, locale, profile);
+            return;
+        }
+        let items: Vec<String> = ITEMS
+            .iter()
+            .map(|item| locale.get(item.text()).to_owned())
+            .collect();
+        self.menu.draw(
+            framebuffer,
+            camera,
+            primitive,
+            mouse_pos,
+            locale.get("pause.title"),
+            &items,
+        );
+    }
+}
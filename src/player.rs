@@ -1,5 +1,6 @@
 use super::*;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
     pub pos: Vec2<f32>,
     pub radius: f32,
@@ -11,6 +12,8 @@ pub struct Player {
     pub stand_timer: f32,
     pub jump: Option<f32>,
     pub want_jump: bool,
+    #[serde(default)]
+    pub jump_buffer: f32,
     pub landed: bool,
     pub eaten: bool,
     pub almost_dead: bool,
@@ -32,6 +35,7 @@ impl Player {
             stand_timer: 0.0,
             jump: None,
             want_jump: false,
+            jump_buffer: 0.0,
             landed: false,
             eaten: false,
             alive: true,
@@ -48,16 +52,49 @@ impl Player {
             false
         }
     }
-    pub fn update(&mut self, delta_time: f32, assets: &Assets) {
+    pub fn has(&self, ability: Ability) -> bool {
+        self.mutation
+            .map_or(false, |mutation| mutation.abilities().contains(&ability))
+    }
+    pub fn hurt(
+        &mut self,
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+        events: &mut Vec<GameEvent>,
+    ) -> bool {
+        if self.almost_dead {
+            events.push(GameEvent::PlayerDied {
+                pos: self.pos,
+                mutation: self.mutation,
+            });
+            self.alive = false;
+            false
+        } else {
+            self.almost_dead = true;
+            events.push(GameEvent::PlayerHit { pos, mutation });
+            true
+        }
+    }
+    pub fn update(&mut self, delta_time: f32, events: &mut Vec<GameEvent>) {
         if !self.alive {
             return;
         }
-        if self.stage >= Stage::Born && (self.want_jump || self.jump.is_some()) {
+        const JUMP_BUFFER_TIME: f32 = 0.15;
+        if self.want_jump {
+            self.jump_buffer = JUMP_BUFFER_TIME;
+        }
+        if self.stage >= Stage::Born && (self.jump_buffer > 0.0 || self.jump.is_some()) {
             if self.jump.is_none() {
-                assets.jump.play();
+                events.push(GameEvent::Jumped { pos: self.pos });
                 self.jump = Some(1.0);
+                self.jump_buffer = 0.0;
             }
-            let time_left = self.jump.unwrap() - delta_time * 3.0;
+            let airtime = if self.has(Ability::Airtime) {
+                AIRTIME_FACTOR
+            } else {
+                1.0
+            };
+            let time_left = self.jump.unwrap() - delta_time * 3.0 / airtime;
             if time_left < 0.0 {
                 self.jump = None;
                 self.landed = true;
@@ -67,13 +104,19 @@ impl Player {
         }
         if self.jump.is_none() {
             const ACCEL: f32 = 20.0;
-            let dv = self.target_vel * self.max_speed - self.vel;
+            let max_speed = if self.has(Ability::Speed) {
+                self.max_speed * SPEED_FACTOR
+            } else {
+                self.max_speed
+            };
+            let dv = self.target_vel * max_speed - self.vel;
             if dv.len() > 1e-5 {
                 self.vel += dv.normalize() * (ACCEL * delta_time).min(dv.len())
             }
         }
         self.pos += self.vel * delta_time;
         self.want_jump = false;
+        self.jump_buffer = (self.jump_buffer - delta_time).max(0.0);
         if self.vel.len() > 1e-5 {
             self.stand_timer -= delta_time * 5.0;
         } else {
@@ -97,23 +140,17 @@ impl Player {
             radius *= 1.0 + (self.leg_walk_phase / 4.0).cos() * 0.1;
         }
         let mut pos_with_jump = self.pos;
-        for (i, &mutation) in [
-            Mutation::RGB,
-            Mutation::Red,
-            Mutation::Yellow,
-            Mutation::Green,
-            Mutation::Cyan,
-            Mutation::Blue,
-            Mutation::Purple,
-        ]
-        .iter()
-        .enumerate()
-        {
-            if self.tropheys.contains(&mutation) {
+        let tropheys = Mutation::all();
+        for (i, mutation) in tropheys.iter().enumerate() {
+            if self.tropheys.contains(mutation) {
                 primitive.circle(
                     framebuffer,
                     camera,
-                    pos_with_jump + vec2(i as f32 / 6.0 * 2.0 - 1.0, 1.0) * self.radius,
+                    pos_with_jump
+                        + vec2(
+                            i as f32 / (tropheys.len() - 1).max(1) as f32 * 2.0 - 1.0,
+                            1.0,
+                        ) * self.radius,
                     self.radius * 0.2,
                     mutation.color(),
                 );
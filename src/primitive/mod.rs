@@ -7,8 +7,44 @@ pub struct Vertex {
     pub a_color: Color<f32>,
 }
 
-pub struct Primitive {
+const FONTS: [&[u8]; 2] = [
+    include_bytes!("../../static/Simply Rounded Bold.ttf"),
+    include_bytes!("../../static/DejaVuSans-Bold.ttf"),
+];
+
+struct Font {
     font: geng::Font,
+    glyphs: rusttype::Font<'static>,
+}
+
+impl Font {
+    fn new(geng: &Rc<Geng>, data: &'static [u8]) -> Self {
+        Self {
+            font: geng::Font::new(geng, data.to_vec()).unwrap(),
+            glyphs: rusttype::Font::try_from_bytes(data).unwrap(),
+        }
+    }
+    fn has_glyph(&self, c: char) -> bool {
+        self.glyphs.glyph(c).id().0 != 0
+    }
+    fn width(&self, text: &str, size: f32) -> f32 {
+        let scale = rusttype::Scale::uniform(size);
+        let mut width = 0.0;
+        let mut last = None;
+        for c in text.chars() {
+            let glyph = self.glyphs.glyph(c).scaled(scale);
+            if let Some(last) = last {
+                width += self.glyphs.pair_kerning(scale, last, glyph.id());
+            }
+            width += glyph.h_metrics().advance_width;
+            last = Some(glyph.id());
+        }
+        width
+    }
+}
+
+pub struct Primitive {
+    fonts: Vec<Font>,
     geometry: RefCell<ugli::VertexBuffer<Vertex>>,
     program: ugli::Program,
     texts: RefCell<Vec<(String, Vec2<f32>, f32, Color<f32>)>>,
@@ -17,11 +53,7 @@ pub struct Primitive {
 impl Primitive {
     pub fn new(geng: &Rc<Geng>) -> Self {
         Self {
-            font: geng::Font::new(
-                geng,
-                include_bytes!("../../static/Simply Rounded Bold.ttf").to_vec(),
-            )
-            .unwrap(),
+            fonts: FONTS.iter().map(|data| Font::new(geng, data)).collect(),
             geometry: RefCell::new(ugli::VertexBuffer::new_dynamic(geng.ugli(), vec![])),
             program: geng
                 .shader_lib()
@@ -45,10 +77,44 @@ impl Primitive {
             },
         );
         geom.clear();
-        for (text, pos, size, color) in self.texts.borrow_mut().drain(..) {
-            self.font.draw(framebuffer, &text, pos, size, color);
+        for (text, mut pos, size, color) in self.texts.borrow_mut().drain(..) {
+            for (font, text) in self.runs(&text) {
+                let font = &self.fonts[font];
+                font.font.draw(framebuffer, text, pos, size, color);
+                pos.x += font.width(text, size);
+            }
         }
     }
+    fn runs<'a>(&self, text: &'a str) -> Vec<(usize, &'a str)> {
+        let mut runs = Vec::new();
+        let mut current = (0, 0);
+        for (index, c) in text.char_indices() {
+            if c.is_whitespace() {
+                continue;
+            }
+            let font = self
+                .fonts
+                .iter()
+                .position(|font| font.has_glyph(c))
+                .unwrap_or(0);
+            if font != current.0 {
+                if index > current.1 {
+                    runs.push((current.0, &text[current.1..index]));
+                }
+                current = (font, index);
+            }
+        }
+        if text.len() > current.1 {
+            runs.push((current.0, &text[current.1..]));
+        }
+        runs
+    }
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        self.runs(text)
+            .into_iter()
+            .map(|(font, text)| self.fonts[font].width(text, size))
+            .sum()
+    }
     pub fn text(
         &self,
         framebuffer: &mut ugli::Framebuffer,
@@ -70,7 +136,7 @@ impl Primitive {
         pos: Vec2<f32>,
         size: f32,
     ) {
-        let text_width = self.font.measure(&text, 64.0).width() * size / 64.0;
+        let text_width = self.text_width(&text, size);
         let x_align = (clamp((pos.x - camera.center.x) / camera.fov * 2.0, -1.0..=1.0) + 1.0) / 2.0;
         let pos = vec2(pos.x - text_width * x_align, pos.y + size);
         let cnt = (text_width / size).ceil() as usize;
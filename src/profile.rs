@@ -0,0 +1,180 @@
+use super::*;
+
+const FAST_WIN_TIME: f32 = 10.0 * 60.0;
+const POOP_GOAL: usize = 50;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Achievement {
+    Flawless,
+    Speedy,
+    Prolific,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 3] = [
+        Achievement::Flawless,
+        Achievement::Speedy,
+        Achievement::Prolific,
+    ];
+    pub fn text(&self) -> &'static str {
+        match self {
+            Self::Flawless => "achievement.flawless",
+            Self::Speedy => "achievement.speedy",
+            Self::Prolific => "achievement.prolific",
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> Option<u64> {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|time| time.as_secs())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now() -> Option<u64> {
+    None
+}
+
+pub fn format_date(time: u64) -> String {
+    let z = (time / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default)]
+    pub tropheys: HashMap<Mutation, Option<u64>>,
+    #[serde(default)]
+    pub achievements: HashMap<Achievement, Option<u64>>,
+    #[serde(default)]
+    pub poops: usize,
+}
+
+impl Profile {
+    const FILE_NAME: &'static str = "profile.json";
+    pub fn load(dir: &std::path::Path) -> Result<Self, String> {
+        load_json(dir, Self::FILE_NAME).map_err(|e| format!("Failed to read profile: {}", e))
+    }
+    pub fn save(&self, dir: &std::path::Path) {
+        if let Err(e) = save_json(dir, Self::FILE_NAME, self) {
+            eprintln!("Failed to save profile: {}", e);
+        }
+    }
+    fn unlock(&mut self, achievement: Achievement) -> bool {
+        if self.achievements.contains_key(&achievement) {
+            return false;
+        }
+        self.achievements.insert(achievement, now());
+        true
+    }
+    pub fn handle_event(&mut self, event: &GameEvent, stats: &Stats) -> bool {
+        match *event {
+            GameEvent::TropheyCollected { mutation, .. } => {
+                if self.tropheys.contains_key(&mutation) {
+                    return false;
+                }
+                self.tropheys.insert(mutation, now());
+                true
+            }
+            GameEvent::Pooped { .. } => {
+                self.poops += 1;
+                self.poops >= POOP_GOAL && self.unlock(Achievement::Prolific)
+            }
+            GameEvent::Won => {
+                let mut changed = false;
+                if stats.hits_taken == 0 {
+                    changed |= self.unlock(Achievement::Flawless);
+                }
+                if stats.time < FAST_WIN_TIME {
+                    changed |= self.unlock(Achievement::Speedy);
+                }
+                changed
+            }
+            _ => false,
+        }
+    }
+    pub fn progress(&self, achievement: Achievement) -> Option<String> {
+        match achievement {
+            Achievement::Prolific => Some(format!("{}/{}", self.poops.min(POOP_GOAL), POOP_GOAL)),
+            Achievement::Flawless | Achievement::Speedy => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_unlock_once() {
+        let mut profile = Profile::default();
+        let stats = Stats {
+            time: 100.0,
+            ..Stats::default()
+        };
+        let pos = vec2(0.0, 0.0);
+        let red = Mutation::from_name("Red").unwrap();
+        let trophey = GameEvent::TropheyCollected { pos, mutation: red };
+        assert!(profile.handle_event(&trophey, &stats));
+        assert!(!profile.handle_event(&trophey, &stats));
+        assert!(profile.handle_event(&GameEvent::Won, &stats));
+        assert!(profile.achievements.contains_key(&Achievement::Flawless));
+        assert!(profile.achievements.contains_key(&Achievement::Speedy));
+        assert!(!profile.handle_event(&GameEvent::Won, &stats));
+        let poop = GameEvent::Pooped {
+            pos,
+            mutation: None,
+        };
+        let unlocks: Vec<bool> = (0..POOP_GOAL + 1)
+            .map(|_| profile.handle_event(&poop, &stats))
+            .collect();
+        assert_eq!(unlocks.iter().filter(|&&unlocked| unlocked).count(), 1);
+        assert!(unlocks[POOP_GOAL - 1]);
+        assert_eq!(
+            profile.progress(Achievement::Prolific).unwrap(),
+            format!("{}/{}", POOP_GOAL, POOP_GOAL)
+        );
+    }
+
+    #[test]
+    fn slow_or_hurt_wins_do_not_unlock() {
+        let mut profile = Profile::default();
+        let stats = Stats {
+            time: FAST_WIN_TIME + 1.0,
+            hits_taken: 1,
+            ..Stats::default()
+        };
+        assert!(!profile.handle_event(&GameEvent::Won, &stats));
+        assert!(profile.achievements.is_empty());
+    }
+
+    #[test]
+    fn unknown_tropheys_fail_to_load() {
+        let dir = std::env::temp_dir().join(format!("egg-farm-profile-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(Profile::load(&dir).unwrap().tropheys.is_empty());
+        let path = dir.join(Profile::FILE_NAME);
+        std::fs::write(&path, r#"{"tropheys": {"Teal": null}}"#).unwrap();
+        let result = Profile::load(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+        assert_eq!(format_date(1_700_000_000), "2023-11-14");
+    }
+}
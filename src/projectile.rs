@@ -1,5 +1,20 @@
 use super::*;
 
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Behaviour {
+    Straight,
+    Homing { turn_rate: f32 },
+    Bouncing { bounces: usize },
+    Mine { drag: f32 },
+}
+
+impl Default for Behaviour {
+    fn default() -> Self {
+        Self::Straight
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Projectile {
     pub pos: Vec2<f32>,
     pub radius: f32,
@@ -7,6 +22,10 @@ pub struct Projectile {
     pub alive: bool,
     pub spawn: Vec2<usize>,
     pub mutation: Option<Mutation>,
+    #[serde(default)]
+    pub behaviour: Behaviour,
+    #[serde(default)]
+    pub lifetime: Option<f32>,
 }
 
 impl Projectile {
@@ -18,10 +37,56 @@ impl Projectile {
             alive: true,
             spawn: pos.map(|x| x as usize),
             mutation,
+            behaviour: Behaviour::Straight,
+            lifetime: None,
         }
     }
-    pub fn update(&mut self, delta_time: f32) {
+    pub fn with_behaviour(self, behaviour: Behaviour) -> Self {
+        Self { behaviour, ..self }
+    }
+    pub fn with_lifetime(self, lifetime: f32) -> Self {
+        Self {
+            lifetime: Some(lifetime),
+            ..self
+        }
+    }
+    pub fn take_bounce(&mut self) -> bool {
+        match &mut self.behaviour {
+            Behaviour::Bouncing { bounces } if *bounces > 0 => {
+                *bounces -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+    pub fn update(&mut self, delta_time: f32, target: Vec2<f32>) {
+        match &mut self.behaviour {
+            Behaviour::Straight => {}
+            Behaviour::Homing { turn_rate } => {
+                let speed = self.vel.len();
+                let desired = target - self.pos;
+                if speed > 1e-5 && desired.len() > 1e-5 {
+                    use std::f32::consts::PI;
+                    let current = self.vel.y.atan2(self.vel.x);
+                    let diff =
+                        (desired.y.atan2(desired.x) - current + PI).rem_euclid(2.0 * PI) - PI;
+                    let max_turn = *turn_rate * delta_time;
+                    let angle = current + clamp(diff, -max_turn..=max_turn);
+                    self.vel = vec2(angle.cos(), angle.sin()) * speed;
+                }
+            }
+            Behaviour::Mine { drag } => {
+                self.vel *= (1.0 - *drag * delta_time).max(0.0);
+            }
+            Behaviour::Bouncing { .. } => {}
+        }
         self.pos += self.vel * delta_time;
+        if let Some(lifetime) = &mut self.lifetime {
+            *lifetime -= delta_time;
+            if *lifetime <= 0.0 {
+                self.alive = false;
+            }
+        }
     }
     pub fn draw(
         &self,
@@ -29,13 +94,54 @@ impl Projectile {
         camera: &Camera,
         primitive: &Primitive,
     ) {
-        primitive.circle(framebuffer, camera, self.pos, self.radius, Color::BLACK);
+        let radius = self.lifetime.map_or(self.radius, |lifetime| {
+            self.radius * clamp(lifetime * 2.0, 0.5..=1.0)
+        });
+        match self.behaviour {
+            Behaviour::Straight | Behaviour::Bouncing { .. } => {}
+            Behaviour::Homing { .. } => {
+                if self.vel.len() > 1e-5 {
+                    primitive.line(
+                        framebuffer,
+                        camera,
+                        self.pos,
+                        self.pos - self.vel.normalize() * radius * 2.0,
+                        radius * 0.5,
+                        Color::BLACK,
+                    );
+                }
+            }
+            Behaviour::Mine { .. } => {
+                for &dir in &[vec2(1.0, 1.0), vec2(1.0, -1.0)] {
+                    primitive.line(
+                        framebuffer,
+                        camera,
+                        self.pos - dir * radius,
+                        self.pos + dir * radius,
+                        radius * 0.3,
+                        Color::BLACK,
+                    );
+                }
+            }
+        }
+        primitive.circle(framebuffer, camera, self.pos, radius, Color::BLACK);
         primitive.circle(
             framebuffer,
             camera,
             self.pos,
-            self.radius - 0.1,
+            radius - 0.1,
             self.mutation.map_or(Color::WHITE, |m| m.color()),
         );
+        if let Behaviour::Bouncing { bounces } = self.behaviour {
+            for i in 0..bounces {
+                primitive.circle(
+                    framebuffer,
+                    camera,
+                    self.pos + vec2(i as f32 * 0.1 - (bounces - 1) as f32 * 0.05, 0.0),
+                    0.03,
+                    Color::BLACK,
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,242 @@
+use super::*;
+
+const MAGIC: &[u8; 4] = b"EGGR";
+const MAGIC_WITH_PALETTE: &[u8; 4] = b"EGGP";
+const MAGIC_WITH_LEVEL: &[u8; 4] = b"EGGL";
+
+const UP: u8 = 1 << 0;
+const DOWN: u8 = 1 << 1;
+const LEFT: u8 = 1 << 2;
+const RIGHT: u8 = 1 << 3;
+const JUMP: u8 = 1 << 4;
+const RESTART: u8 = 1 << 5;
+const ANALOG: u8 = 1 << 6;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frame {
+    pub delta_time: f32,
+    pub input: Input,
+    pub restart: Option<u64>,
+}
+
+impl Frame {
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        let dir = self.input.move_direction;
+        if dir.y > 0.0 {
+            flags |= UP;
+        }
+        if dir.y < 0.0 {
+            flags |= DOWN;
+        }
+        if dir.x < 0.0 {
+            flags |= LEFT;
+        }
+        if dir.x > 0.0 {
+            flags |= RIGHT;
+        }
+        if self.input.jump {
+            flags |= JUMP;
+        }
+        if self.restart.is_some() {
+            flags |= RESTART;
+        }
+        if !Self::is_digital(dir) {
+            flags |= ANALOG;
+        }
+        flags
+    }
+    fn is_digital(dir: Vec2<f32>) -> bool {
+        [dir.x, dir.y]
+            .iter()
+            .all(|&x| x == 0.0 || x == 1.0 || x == -1.0)
+    }
+    fn input_from_flags(flags: u8) -> Input {
+        let mut move_direction = vec2(0.0, 0.0);
+        if flags & UP != 0 {
+            move_direction.y += 1.0;
+        }
+        if flags & DOWN != 0 {
+            move_direction.y -= 1.0;
+        }
+        if flags & LEFT != 0 {
+            move_direction.x -= 1.0;
+        }
+        if flags & RIGHT != 0 {
+            move_direction.x += 1.0;
+        }
+        Input {
+            move_direction,
+            jump: flags & JUMP != 0,
+        }
+    }
+}
+
+pub struct Recorder {
+    file: std::io::BufWriter<std::fs::File>,
+}
+
+impl Recorder {
+    pub fn create(
+        path: &std::path::Path,
+        seed: u64,
+        palette: &str,
+        level: &str,
+    ) -> std::io::Result<Self> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        file.write_all(MAGIC_WITH_LEVEL)?;
+        file.write_all(&seed.to_le_bytes())?;
+        for text in &[palette, level] {
+            file.write_all(&(text.len() as u32).to_le_bytes())?;
+            file.write_all(text.as_bytes())?;
+        }
+        Ok(Self { file })
+    }
+    pub fn record(&mut self, frame: &Frame) -> std::io::Result<()> {
+        let mut data = Vec::with_capacity(21);
+        data.extend_from_slice(&frame.delta_time.to_le_bytes());
+        let flags = frame.flags();
+        data.push(flags);
+        if flags & ANALOG != 0 {
+            let dir = frame.input.move_direction;
+            data.extend_from_slice(&dir.x.to_le_bytes());
+            data.extend_from_slice(&dir.y.to_le_bytes());
+        }
+        if let Some(seed) = frame.restart {
+            data.extend_from_slice(&seed.to_le_bytes());
+        }
+        self.file.write_all(&data)
+    }
+}
+
+pub struct Playback {
+    seed: u64,
+    palette: Option<String>,
+    level: Option<String>,
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl Playback {
+    pub fn open(path: &std::path::Path) -> std::io::Result<Self> {
+        let invalid = |message: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+        let data = std::fs::read(path)?;
+        let (with_palette, with_level) = match data.get(..MAGIC.len()) {
+            Some(magic) if magic == MAGIC => (false, false),
+            Some(magic) if magic == MAGIC_WITH_PALETTE => (true, false),
+            Some(magic) if magic == MAGIC_WITH_LEVEL => (true, true),
+            _ => return Err(invalid("Not a replay file")),
+        };
+        let mut playback = Self {
+            seed: 0,
+            palette: None,
+            level: None,
+            data,
+            pos: MAGIC.len(),
+        };
+        playback.seed =
+            u64::from_le_bytes(playback.take().ok_or_else(|| invalid("Truncated replay"))?);
+        if with_palette {
+            playback.palette = Some(playback.take_string().map_err(invalid)?);
+        }
+        if with_level {
+            playback.level = Some(playback.take_string().map_err(invalid)?);
+        }
+        Ok(playback)
+    }
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    pub fn palette(&self) -> Option<&str> {
+        self.palette.as_deref()
+    }
+    pub fn level(&self) -> Option<&str> {
+        self.level.as_deref()
+    }
+    fn take_string(&mut self) -> Result<String, &'static str> {
+        let len = u32::from_le_bytes(self.take().ok_or("Truncated replay")?) as usize;
+        let text = self
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or("Truncated replay")?;
+        let text = String::from_utf8(text.to_vec()).map_err(|_| "Invalid replay header")?;
+        self.pos += len;
+        Ok(text)
+    }
+    fn take<T: Default + AsMut<[u8]>>(&mut self) -> Option<T> {
+        let mut bytes = T::default();
+        let len = bytes.as_mut().len();
+        let data = self.data.get(self.pos..self.pos + len)?;
+        bytes.as_mut().copy_from_slice(data);
+        self.pos += len;
+        Some(bytes)
+    }
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        let delta_time = f32::from_le_bytes(self.take()?);
+        let [flags] = self.take::<[u8; 1]>()?;
+        let mut input = Frame::input_from_flags(flags);
+        if flags & ANALOG != 0 {
+            input.move_direction = vec2(
+                f32::from_le_bytes(self.take()?),
+                f32::from_le_bytes(self.take()?),
+            );
+        }
+        let restart = if flags & RESTART != 0 {
+            Some(u64::from_le_bytes(self.take()?))
+        } else {
+            None
+        };
+        Some(Frame {
+            delta_time,
+            input,
+            restart,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_round_trip() {
+        let path = std::env::temp_dir().join(format!("egg-farm-{}.replay", std::process::id()));
+        let frames = [
+            Frame {
+                delta_time: 1.0 / 60.0,
+                input: Input {
+                    move_direction: vec2(1.0, 0.0),
+                    jump: true,
+                },
+                restart: None,
+            },
+            Frame {
+                delta_time: 0.1,
+                input: Input {
+                    move_direction: vec2(0.3, -0.7),
+                    jump: false,
+                },
+                restart: Some(3),
+            },
+            Frame {
+                delta_time: 0.05,
+                input: Input::default(),
+                restart: None,
+            },
+        ];
+        let mut recorder = Recorder::create(&path, 42, "palette", "level").unwrap();
+        for frame in &frames {
+            recorder.record(frame).unwrap();
+        }
+        drop(recorder);
+        let mut playback = Playback::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(playback.seed(), 42);
+        assert_eq!(playback.palette(), Some("palette"));
+        assert_eq!(playback.level(), Some("level"));
+        for frame in &frames {
+            assert_eq!(playback.next_frame().as_ref(), Some(frame));
+        }
+        assert_eq!(playback.next_frame(), None);
+    }
+}
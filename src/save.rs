@@ -0,0 +1,132 @@
+use super::*;
+
+pub const SAVE_SLOTS: usize = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SaveSlot {
+    Auto,
+    Manual(usize),
+}
+
+impl SaveSlot {
+    fn file_name(&self) -> String {
+        match self {
+            Self::Auto => "autosave.json".to_owned(),
+            Self::Manual(index) => format!("save{}.json", index),
+        }
+    }
+}
+
+impl std::str::FromStr for SaveSlot {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        if s == "auto" {
+            return Ok(Self::Auto);
+        }
+        match s.parse() {
+            Ok(index) if index >= 1 && index <= SAVE_SLOTS => Ok(Self::Manual(index)),
+            _ => Err(format!(
+                "Expected \"auto\" or a slot number from 1 to {}",
+                SAVE_SLOTS
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    #[serde(default)]
+    palette: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveData {
+    #[serde(default)]
+    pub palette: Option<String>,
+    pub map: Map,
+    pub player: Player,
+    pub projectiles: Vec<Projectile>,
+    pub step: usize,
+    pub stage: Stage,
+    pub spawn: Vec2<f32>,
+    pub seed: u64,
+    pub rng_seed: u64,
+    pub stats: Stats,
+}
+
+impl SaveData {
+    pub fn write(&self, dir: &std::path::Path, slot: SaveSlot) -> std::io::Result<()> {
+        save_json(dir, &slot.file_name(), self)
+    }
+    pub fn read(dir: &std::path::Path, slot: SaveSlot) -> std::io::Result<Self> {
+        let source = std::fs::read_to_string(dir.join(slot.file_name()))?;
+        let header: SaveHeader = serde_json::from_str(&source)?;
+        let previous = Palette::current();
+        if let Some(palette) = header.palette {
+            Palette::parse(&palette)?.install();
+        }
+        // Mutations are stored by name, so the body is read with the save's palette
+        serde_json::from_str(&source).map_err(|e| {
+            (*previous).clone().install();
+            e.into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_round_trip() {
+        let dir = std::env::temp_dir().join(format!("egg-farm-saves-{}", std::process::id()));
+        let script = Rc::new(Script::default());
+        let mut simulation = Simulation::new(Level::default(), script.clone(), 5);
+        for _ in 0..120 {
+            let input = Input {
+                move_direction: vec2(1.0, 0.5),
+                jump: false,
+            };
+            simulation.update(1.0 / 60.0, input);
+        }
+        simulation.save().write(&dir, SaveSlot::Manual(2)).unwrap();
+        let loaded = Simulation::load(SaveData::read(&dir, SaveSlot::Manual(2)).unwrap(), script);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(loaded.stage, simulation.stage);
+        assert_eq!(loaded.step, simulation.step);
+        assert_eq!(loaded.seed, 5);
+        assert_eq!(loaded.player.pos, simulation.player.pos);
+        assert_eq!(loaded.player.vel, simulation.player.vel);
+        assert_eq!(loaded.map.tiles, simulation.map.tiles);
+        assert_eq!(loaded.map.fertility, simulation.map.fertility);
+        assert_eq!(
+            format!("{:?}", loaded.stats),
+            format!("{:?}", simulation.stats)
+        );
+        assert!(SaveData::read(&dir, SaveSlot::Auto).is_err());
+    }
+
+    #[test]
+    fn broken_saves_keep_the_palette() {
+        let dir = std::env::temp_dir().join(format!("egg-farm-broken-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let palette = r#"{
+            "channels": [{"name": "R", "color": [1, 0, 0]}],
+            "colors": [{"name": "Red", "channels": ["R"]}]
+        }"#;
+        let save = serde_json::json!({ "palette": palette, "map": 5 });
+        std::fs::write(dir.join("autosave.json"), save.to_string()).unwrap();
+        let result = SaveData::read(&dir, SaveSlot::Auto);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_err());
+        assert_eq!(Palette::current().source(), Palette::default().source());
+    }
+
+    #[test]
+    fn slot_names() {
+        assert_eq!("auto".parse(), Ok(SaveSlot::Auto));
+        assert_eq!("4".parse(), Ok(SaveSlot::Manual(4)));
+        assert!("0".parse::<SaveSlot>().is_err());
+        assert!("5".parse::<SaveSlot>().is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use super::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PlayerFlag {
+    Jumped,
+    Eaten,
+    Mutated,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Condition {
+    Moved(f32),
+    Tiles {
+        tile: TileKind,
+        #[serde(default)]
+        mutated: Option<bool>,
+        #[serde(default)]
+        at_least: Option<usize>,
+        #[serde(default)]
+        at_most: Option<usize>,
+    },
+    Player(PlayerFlag),
+    Tropheys(usize),
+    AllTropheys,
+}
+
+impl Condition {
+    pub fn check(&self, simulation: &Simulation) -> bool {
+        match *self {
+            Self::Moved(distance) => (simulation.player.pos - simulation.spawn).len() > distance,
+            Self::Tiles {
+                tile,
+                mutated,
+                at_least,
+                at_most,
+            } => {
+                let count = simulation.map.find(|other| {
+                    other.kind() == tile
+                        && mutated.map_or(true, |mutated| other.mutation().is_some() == mutated)
+                });
+                count >= at_least.unwrap_or(0) && count <= at_most.unwrap_or(count)
+            }
+            Self::Player(flag) => {
+                let player = &simulation.player;
+                match flag {
+                    PlayerFlag::Jumped => player.jump.is_some(),
+                    PlayerFlag::Eaten => player.eaten,
+                    PlayerFlag::Mutated => player.mutation.is_some(),
+                }
+            }
+            Self::Tropheys(count) => simulation.player.tropheys.len() >= count,
+            Self::AllTropheys => Mutation::all()
+                .iter()
+                .all(|mutation| simulation.player.tropheys.contains(mutation)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Step {
+    pub stage: Stage,
+    pub help: String,
+    #[serde(default)]
+    pub keys: Vec<Action>,
+    #[serde(default)]
+    pub until: Option<Condition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Script {
+    pub steps: Vec<Step>,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self::parse(include_str!("../scripts/tutorial.json")).unwrap()
+    }
+}
+
+impl Script {
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read script {}: {}", path.display(), e))?;
+        Self::parse(&source).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+    pub fn parse(source: &str) -> Result<Self, serde_json::Error> {
+        let script: Self = serde_json::from_str(source)?;
+        if script.steps.is_empty() {
+            return Err(serde::de::Error::custom("Script has no steps"));
+        }
+        Ok(script)
+    }
+    pub fn first_step(&self, stage: Stage) -> usize {
+        self.steps
+            .iter()
+            .position(|step| step.stage >= stage)
+            .unwrap_or(self.steps.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_tropheys_needs_every_named_color() {
+        Palette::parse(
+            r#"{
+                "channels": [{"name": "R", "color": [1, 0, 0]}, {"name": "B", "color": [0, 0, 1]}],
+                "colors": [{"name": "Red", "channels": ["R"]}, {"name": "Blue", "channels": ["B"]}]
+            }"#,
+        )
+        .unwrap()
+        .install();
+        let mut simulation = Simulation::new(Level::default(), Rc::new(Script::default()), 1);
+        let tropheys = &mut simulation.player.tropheys;
+        tropheys.insert(Mutation::from_name("Red").unwrap());
+        tropheys.insert(Mutation::from_name("R+B").unwrap());
+        assert!(!Condition::AllTropheys.check(&simulation));
+        simulation
+            .player
+            .tropheys
+            .insert(Mutation::from_name("Blue").unwrap());
+        assert!(Condition::AllTropheys.check(&simulation));
+    }
+}
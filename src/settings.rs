@@ -0,0 +1,88 @@
+use super::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioSettings {
+    pub master_volume: f64,
+    pub sfx_volume: f64,
+    pub ui_volume: f64,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            ui_volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplaySettings {
+    pub text_scale: f32,
+    pub particles: bool,
+    #[serde(default)]
+    pub fertility: bool,
+    #[serde(default)]
+    pub timer: bool,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            text_scale: 1.0,
+            particles: true,
+            fertility: false,
+            timer: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(default)]
+    pub audio: AudioSettings,
+    #[serde(default)]
+    pub display: DisplaySettings,
+    #[serde(default)]
+    pub controls: Controls,
+}
+
+impl Settings {
+    const FILE_NAME: &'static str = "settings.json";
+    pub fn load(dir: &std::path::Path) -> Self {
+        load_json(dir, Self::FILE_NAME).unwrap_or_else(|e| {
+            eprintln!("Failed to read settings: {}", e);
+            Self::default()
+        })
+    }
+    pub fn save(&self, dir: &std::path::Path) {
+        if let Err(e) = save_json(dir, Self::FILE_NAME, self) {
+            eprintln!("Failed to save settings: {}", e);
+        }
+    }
+}
+
+// A missing file reads as the default value, so only a broken one is an error.
+pub fn load_json<T: serde::de::DeserializeOwned + Default>(
+    dir: &std::path::Path,
+    file_name: &str,
+) -> serde_json::Result<T> {
+    match std::fs::File::open(dir.join(file_name)) {
+        Ok(file) => serde_json::from_reader(std::io::BufReader::new(file)),
+        Err(_) => Ok(T::default()),
+    }
+}
+
+pub fn save_json<T: Serialize>(
+    dir: &std::path::Path,
+    file_name: &str,
+    value: &T,
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let file = std::fs::File::create(dir.join(file_name))?;
+    serde_json::to_writer_pretty(std::io::BufWriter::new(file), value)?;
+    Ok(())
+}
@@ -0,0 +1,381 @@
+use super::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Input {
+    pub move_direction: Vec2<f32>,
+    pub jump: bool,
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self {
+            move_direction: vec2(0.0, 0.0),
+            jump: false,
+        }
+    }
+}
+
+pub struct Simulation {
+    pub map: Map,
+    pub player: Player,
+    pub projectiles: Vec<Projectile>,
+    pub script: Rc<Script>,
+    pub step: usize,
+    pub stage: Stage,
+    pub spawn: Vec2<f32>,
+    pub seed: u64,
+    pub rng: StdRng,
+    pub stats: Stats,
+    pub events: Vec<GameEvent>,
+    broadphase: Broadphase,
+}
+
+impl Simulation {
+    pub fn new(level: Level, script: Rc<Script>, seed: u64) -> Self {
+        let step = script.first_step(level.stage);
+        let stage = script.steps[step].stage;
+        let mut player = Player::new(level.spawn);
+        if stage >= Stage::Born {
+            player.radius = 0.3;
+        }
+        Self {
+            player,
+            map: level.map,
+            projectiles: Vec::new(),
+            script,
+            step,
+            stage,
+            spawn: level.spawn,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            stats: Stats::default(),
+            events: Vec::new(),
+            broadphase: Broadphase::new(),
+        }
+    }
+    pub fn load(save: SaveData, script: Rc<Script>) -> Self {
+        let mut map = save.map;
+        map.resize(map.size());
+        Self {
+            map,
+            player: save.player,
+            projectiles: save.projectiles,
+            step: min(save.step, script.steps.len() - 1),
+            script,
+            stage: save.stage,
+            spawn: save.spawn,
+            seed: save.seed,
+            rng: StdRng::seed_from_u64(save.rng_seed),
+            stats: save.stats,
+            events: Vec::new(),
+            broadphase: Broadphase::new(),
+        }
+    }
+    pub fn save(&self) -> SaveData {
+        SaveData {
+            palette: Some(Palette::current().source().to_owned()),
+            map: self.map.clone(),
+            player: self.player.clone(),
+            projectiles: self.projectiles.clone(),
+            step: self.step,
+            stage: self.stage,
+            spawn: self.spawn,
+            seed: self.seed,
+            rng_seed: self.rng.clone().gen(),
+            stats: self.stats.clone(),
+        }
+    }
+    pub fn help(&self) -> &str {
+        &self.script.steps[self.step].help
+    }
+    pub fn help_keys(&self) -> &[Action] {
+        &self.script.steps[self.step].keys
+    }
+    fn enter_stage(&mut self, stage: Stage) {
+        if self.stage < Stage::Born && stage >= Stage::Born {
+            self.player.radius = 0.3;
+            self.events.push(GameEvent::Hatched {
+                pos: self.player.pos,
+            });
+        }
+        if self.stage != Stage::Win && stage == Stage::Win {
+            self.events.push(GameEvent::Won);
+        }
+        if self.stage != stage {
+            self.events.push(GameEvent::StageChanged { stage });
+        }
+        self.stage = stage;
+    }
+    pub fn broken_shells(&self) -> usize {
+        self.map.find(|tile| *tile == Tile::BrokenShell)
+    }
+    pub fn text_at(&self, pos: Vec2<f32>) -> &'static str {
+        if let Some(text) = self.map.text_at(pos) {
+            return text;
+        }
+        if (self.player.pos - pos).len() < self.player.radius {
+            return "player";
+        }
+        Tile::Nothing.text()
+    }
+    pub fn update(&mut self, delta_time: f32, input: Input) {
+        let events_start = self.events.len();
+        if self.stage != Stage::Win {
+            self.stats.time += delta_time;
+        }
+        self.update_world(delta_time, input);
+        for event in &self.events[events_start..] {
+            self.stats.handle_event(event);
+        }
+    }
+    fn update_world(&mut self, delta_time: f32, input: Input) {
+        self.player.stage = self.stage;
+        if self.stage == Stage::Win {
+            return;
+        }
+        self.map.update(
+            delta_time,
+            &mut self.projectiles,
+            &mut self.player,
+            &mut self.rng,
+            &mut self.events,
+        );
+        self.player.target_vel = input.move_direction;
+        if self.player.target_vel.len() > 1.0 {
+            self.player.target_vel = self.player.target_vel.normalize();
+        }
+        if input.jump {
+            self.player.want_jump = true;
+        }
+        self.player.update(delta_time, &mut self.events);
+        let script = self.script.clone();
+        while self.step + 1 < script.steps.len() {
+            match &script.steps[self.step].until {
+                Some(condition) if condition.check(self) => {
+                    self.step += 1;
+                    self.enter_stage(script.steps[self.step].stage);
+                }
+                _ => break,
+            }
+        }
+        // The position is only pushed back inside the map below, so look tiles up by a clamped cell
+        let size = self.map.size();
+        let cell = vec2(
+            min(self.player.pos.x.max(0.0) as usize, size.x - 1),
+            min(self.player.pos.y.max(0.0) as usize, size.y - 1),
+        );
+        let cell_center = cell.map(|x| x as f32 + 0.5);
+        if !self.player.eaten {
+            if let Tile::Food { mutation } = self.map.tiles[cell.x][cell.y] {
+                self.events.push(GameEvent::FoodEaten {
+                    pos: self.player.pos,
+                    mutation,
+                });
+                self.player.eaten = true;
+                self.player.almost_dead = false;
+                self.map.harvest(cell_center, mutation, &self.player);
+                if let Some(mutation) = mutation {
+                    self.player.mutation = mutation.mix(self.player.mutation);
+                }
+            }
+        }
+        if self.player.landed() {
+            if self.player.eaten && self.map.tiles[cell.x][cell.y] == Tile::Nothing {
+                self.player.eaten = false;
+                self.events.push(GameEvent::Pooped {
+                    pos: self.player.pos,
+                    mutation: self.player.mutation,
+                });
+                self.map.tiles[cell.x][cell.y] = Tile::Poop {
+                    mutation: self.player.mutation,
+                };
+                self.player.mutation = None;
+            } else {
+                self.map.land(
+                    cell_center,
+                    &mut self.player,
+                    &mut self.rng,
+                    &mut self.events,
+                );
+            }
+        }
+        let mut fix_pos = self.player.pos;
+        if fix_pos.x < self.player.radius {
+            fix_pos.x = self.player.radius;
+        }
+        if fix_pos.y < self.player.radius {
+            fix_pos.y = self.player.radius;
+        }
+        if fix_pos.x > self.map.size().x as f32 - self.player.radius {
+            fix_pos.x = self.map.size().x as f32 - self.player.radius;
+        }
+        if fix_pos.y > self.map.size().y as f32 - self.player.radius {
+            fix_pos.y = self.map.size().y as f32 - self.player.radius;
+        }
+        let fix_pos = self.map.push_out(fix_pos, self.player.radius);
+        if fix_pos != self.player.pos {
+            self.player.pos = fix_pos;
+            if self.player.vel.len() > self.player.max_speed / 2.0 && self.stage == Stage::Moving {
+                self.events.push(GameEvent::ShellCracked {
+                    pos: self.player.pos,
+                });
+                let mut shell_pos = Vec::new();
+                for dx in -1..=1 {
+                    for dy in -1..=1 {
+                        let x = self.player.pos.x as i32 + dx;
+                        let y = self.player.pos.y as i32 + dy;
+                        if x >= 0
+                            && x < self.map.size().x as _
+                            && y >= 0
+                            && y < self.map.size().y as _
+                        {
+                            shell_pos.push(vec2(x as usize, y as usize));
+                        }
+                    }
+                }
+                use rand::seq::SliceRandom;
+                shell_pos.shuffle(&mut self.rng);
+                for pos in shell_pos {
+                    if self.map.tiles[pos.x][pos.y] == Tile::Nothing {
+                        self.map.tiles[pos.x][pos.y] = Tile::BrokenShell;
+                        self.events.push(GameEvent::ShellDropped {
+                            pos: pos.map(|x| x as f32 + 0.5),
+                        });
+                        break;
+                    }
+                }
+            }
+            self.player.vel = vec2(0.0, 0.0);
+        }
+        self.broadphase.rebuild(self.map.size(), &self.projectiles);
+        for (i, j) in self.broadphase.pairs(&self.projectiles) {
+            if self.projectiles[i].mutation == self.projectiles[j].mutation
+                && self.projectiles[i].spawn != self.projectiles[j].spawn
+            {
+                self.projectiles[i].alive = false;
+                self.projectiles[j].alive = false;
+            }
+        }
+        for i in self.broadphase.query(self.player.pos, self.player.radius) {
+            let p = &mut self.projectiles[i];
+            if self.player.alive && (p.pos - self.player.pos).len() < p.radius + self.player.radius
+            {
+                p.alive = false;
+                if self.player.hurt(p.pos, p.mutation, &mut self.events) {
+                    self.player.vel += p.vel * 2.0;
+                }
+            }
+        }
+        for p in &mut self.projectiles {
+            let from = p.pos;
+            p.update(delta_time, self.player.pos);
+            self.map.collide_projectile(from, p, &mut self.events);
+            if p.pos.x < 0.0
+                || p.pos.y < 0.0
+                || p.pos.x >= self.map.size().x as f32
+                || p.pos.y >= self.map.size().y as f32
+            {
+                p.alive = false;
+            }
+            if !p.alive {
+                self.events.push(GameEvent::ProjectileDestroyed {
+                    pos: p.pos,
+                    mutation: p.mutation,
+                });
+            }
+        }
+        self.projectiles.retain(|p| p.alive);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELTA_TIME: f32 = 1.0 / 60.0;
+
+    // Runs back and forth between the side walls, jumping every few seconds
+    fn input(frame: usize) -> Input {
+        let direction = if (frame / 300) % 2 == 0 { 1.0 } else { -1.0 };
+        Input {
+            move_direction: vec2(direction, 0.0),
+            jump: frame % 150 == 0,
+        }
+    }
+
+    fn run(seed: u64, frames: usize) -> Simulation {
+        let mut simulation = Simulation::new(Level::default(), Rc::new(Script::default()), seed);
+        for frame in 0..frames {
+            simulation.update(DELTA_TIME, input(frame));
+        }
+        simulation
+    }
+
+    #[test]
+    fn same_seed_and_input_give_the_same_run() {
+        let a = run(42, 3000);
+        let b = run(42, 3000);
+        assert_eq!(a.events, b.events);
+        assert_eq!(a.stage, b.stage);
+        assert_eq!(a.step, b.step);
+        assert_eq!(a.player.pos, b.player.pos);
+        assert_eq!(a.player.vel, b.player.vel);
+        assert_eq!(a.map.tiles, b.map.tiles);
+        assert_eq!(a.map.fertility, b.map.fertility);
+        assert_eq!(
+            a.projectiles.iter().map(|p| p.pos).collect::<Vec<_>>(),
+            b.projectiles.iter().map(|p| p.pos).collect::<Vec<_>>(),
+        );
+        assert_eq!(format!("{:?}", a.stats), format!("{:?}", b.stats));
+    }
+
+    #[test]
+    fn large_steps_do_not_leave_the_map() {
+        let mut simulation = Simulation::new(Level::default(), Rc::new(Script::default()), 3);
+        simulation.player.eaten = true;
+        simulation.player.landed = true;
+        for direction in &[vec2(1.0, 1.0), vec2(-1.0, -1.0)] {
+            simulation.player.vel = *direction * 1000.0;
+            simulation.update(
+                1.0,
+                Input {
+                    move_direction: *direction,
+                    jump: false,
+                },
+            );
+            let size = simulation.map.size().map(|x| x as f32);
+            let pos = simulation.player.pos;
+            assert!(pos.x > 0.0 && pos.y > 0.0 && pos.x < size.x && pos.y < size.y);
+        }
+    }
+
+    #[test]
+    fn stages_follow_the_script() {
+        let mut simulation = Simulation::new(Level::default(), Rc::new(Script::default()), 7);
+        assert_eq!(simulation.stage, Stage::Start);
+        let mut stages = vec![simulation.stage];
+        for frame in 0..3000 {
+            simulation.update(DELTA_TIME, input(frame));
+            if simulation.stage != *stages.last().unwrap() {
+                stages.push(simulation.stage);
+            }
+        }
+        assert_eq!(
+            stages[..4],
+            [Stage::Start, Stage::Moving, Stage::Born, Stage::ToCrush]
+        );
+        assert!(
+            simulation.broken_shells() + simulation.map.find(|tile| *tile == Tile::CrushedShell)
+                >= 3
+        );
+        let changes: Vec<Stage> = simulation
+            .events
+            .iter()
+            .filter_map(|event| match *event {
+                GameEvent::StageChanged { stage } => Some(stage),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(changes, stages[1..]);
+    }
+}
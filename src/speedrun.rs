@@ -0,0 +1,191 @@
+use super::*;
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct Split {
+    pub stage: Stage,
+    pub time: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Run {
+    pub level: String,
+    pub seed: u64,
+    pub splits: Vec<Split>,
+}
+
+impl Run {
+    fn time(&self) -> Option<f32> {
+        self.splits
+            .iter()
+            .find(|split| split.stage == Stage::Win)
+            .map(|split| split.time)
+    }
+}
+
+pub fn content_key<T: Serialize>(value: &T) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in &serde_json::to_vec(value).unwrap() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+pub fn level_key(level: &Level) -> String {
+    content_key(&(&level.map.tiles, level.spawn, level.stage))
+}
+
+pub fn format_time(time: f32) -> String {
+    let centis = (time.max(0.0) * 100.0).round() as u64;
+    format!(
+        "{}:{:02}.{:02}",
+        centis / 6000,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+pub struct Speedrun {
+    run: Run,
+    ranked: bool,
+    bests: HashMap<String, Run>,
+}
+
+impl Speedrun {
+    const FILE_NAME: &'static str = "speedruns.json";
+    pub fn new(dir: &std::path::Path, level: &Level, seed: u64) -> Self {
+        let bests = load_json(dir, Self::FILE_NAME).unwrap_or_else(|e| {
+            eprintln!("Failed to read speedruns: {}", e);
+            HashMap::new()
+        });
+        let mut speedrun = Self {
+            run: Run {
+                level: String::new(),
+                seed,
+                splits: Vec::new(),
+            },
+            ranked: true,
+            bests,
+        };
+        speedrun.reset(level, seed);
+        speedrun
+    }
+    fn key(&self) -> String {
+        format!("{}-{}", self.run.level, self.run.seed)
+    }
+    pub fn reset(&mut self, level: &Level, seed: u64) {
+        self.run = Run {
+            level: level_key(level),
+            seed,
+            splits: Vec::new(),
+        };
+        self.ranked = true;
+    }
+    pub fn unrank(&mut self) {
+        self.ranked = false;
+    }
+    pub fn best(&self) -> Option<&Run> {
+        self.bests.get(&self.key())
+    }
+    pub fn split(&mut self, stage: Stage, time: f32) -> bool {
+        self.run.splits.push(Split { stage, time });
+        if stage != Stage::Win || !self.ranked {
+            return false;
+        }
+        if self
+            .best()
+            .and_then(|best| best.time())
+            .map_or(false, |best| best <= time)
+        {
+            return false;
+        }
+        self.bests.insert(self.key(), self.run.clone());
+        true
+    }
+    pub fn save(&self, dir: &std::path::Path) {
+        if let Err(e) = save_json(dir, Self::FILE_NAME, &self.bests) {
+            eprintln!("Failed to save speedruns: {}", e);
+        }
+    }
+    pub fn export(&self, dir: &std::path::Path) -> std::io::Result<std::path::PathBuf> {
+        let file_name = format!("splits-{}.json", self.run.seed);
+        save_json(dir, &file_name, &self.run)?;
+        Ok(dir.join(file_name))
+    }
+    pub fn lines(&self, time: f32, locale: &Locale) -> Vec<String> {
+        let best = self.best();
+        let mut lines = vec![format_time(time)];
+        for split in self.run.splits.iter().rev().take(3) {
+            let mut line = format!(
+                "{} {}",
+                locale.get(split.stage.text()),
+                format_time(split.time)
+            );
+            if let Some(best) =
+                best.and_then(|best| best.splits.iter().find(|other| other.stage == split.stage))
+            {
+                let delta = split.time - best.time;
+                line += &format!(
+                    " ({}{})",
+                    if delta < 0.0 { "-" } else { "+" },
+                    format_time(delta.abs())
+                );
+            }
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speedrun(level: &Level, seed: u64) -> Speedrun {
+        let dir = std::env::temp_dir().join("egg-farm-no-such-dir");
+        Speedrun::new(&dir, level, seed)
+    }
+
+    #[test]
+    fn only_faster_wins_are_bests() {
+        let level = Level::default();
+        let mut run = speedrun(&level, 5);
+        assert!(run.best().is_none());
+        assert!(!run.split(Stage::Moving, 1.0));
+        assert!(run.split(Stage::Win, 10.0));
+        run.reset(&level, 5);
+        assert!(!run.split(Stage::Win, 11.0));
+        assert_eq!(run.best().and_then(|best| best.time()), Some(10.0));
+        run.reset(&level, 5);
+        assert!(run.split(Stage::Win, 9.0));
+        assert_eq!(run.best().unwrap().splits.len(), 1);
+        run.reset(&level, 6);
+        assert!(run.best().is_none());
+    }
+
+    #[test]
+    fn unranked_runs_are_not_bests() {
+        let level = Level::default();
+        let mut run = speedrun(&level, 5);
+        run.unrank();
+        assert!(!run.split(Stage::Win, 10.0));
+        assert!(run.best().is_none());
+        run.reset(&level, 5);
+        assert!(run.split(Stage::Win, 10.0));
+    }
+
+    #[test]
+    fn levels_are_told_apart_by_their_tiles() {
+        let level = Level::default();
+        let mut other = level.clone();
+        assert_eq!(level_key(&level), level_key(&other));
+        other.map.tiles[0][0] = Tile::Wall;
+        assert_ne!(level_key(&level), level_key(&other));
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(format_time(83.456), "1:23.46");
+        assert_eq!(format_time(-1.0), "0:00.00");
+    }
+}
@@ -0,0 +1,60 @@
+use super::*;
+
+const SIZE: usize = 64;
+const SAFE_RADIUS: f32 = 3.0;
+
+pub struct Stress {
+    count: usize,
+    update_time: f64,
+}
+
+impl Stress {
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            update_time: 0.0,
+        }
+    }
+    pub fn level() -> Level {
+        Level {
+            map: Map::from_tiles(vec![vec![Tile::Nothing; SIZE]; SIZE], 0),
+            spawn: vec2(SIZE as f32, SIZE as f32) / 2.0,
+            stage: Stage::KillAll,
+        }
+    }
+    pub fn update(&mut self, simulation: &mut Simulation, update_time: f64) {
+        self.update_time += (update_time - self.update_time) * 0.1;
+        let size = simulation.map.size().map(|x| x as f32);
+        while simulation.projectiles.len() < self.count {
+            let rng = &mut simulation.rng;
+            let pos = vec2(rng.gen_range(0.0..size.x), rng.gen_range(0.0..size.y));
+            if (pos - simulation.player.pos).len() < SAFE_RADIUS {
+                continue;
+            }
+            let vel = Vec2::rotated(
+                vec2(rng.gen_range(1.0..3.0), 0.0),
+                rng.gen_range(0.0..2.0 * std::f32::consts::PI),
+            );
+            let mutation = if rng.gen() { Some(rng.gen()) } else { None };
+            let behaviour = match rng.gen_range(0..4) {
+                0 => Behaviour::Straight,
+                1 => Behaviour::Homing { turn_rate: 1.0 },
+                2 => Behaviour::Bouncing { bounces: 3 },
+                _ => Behaviour::Mine { drag: 0.5 },
+            };
+            let lifetime = rng.gen_range(2.0..8.0);
+            simulation.projectiles.push(
+                Projectile::new(pos, 0.2, vel, mutation)
+                    .with_behaviour(behaviour)
+                    .with_lifetime(lifetime),
+            );
+        }
+    }
+    pub fn text(&self, simulation: &Simulation) -> String {
+        format!(
+            "{} projectiles, {:.2} ms",
+            simulation.projectiles.len(),
+            self.update_time * 1000.0
+        )
+    }
+}
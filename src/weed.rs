@@ -0,0 +1,166 @@
+use super::*;
+
+const SPREADER_SPREAD: f32 = std::f32::consts::PI / 3.0;
+const SPRINKLER_SHOTS: usize = 8;
+const BURST_SHOTS: usize = 3;
+const BURST_INTERVAL: f32 = 0.15;
+const SNIPER_AIM_TIME: f32 = 1.0;
+const MINE_LIFETIME: f32 = 6.0;
+const HOMING_LIFETIME: f32 = 3.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WeedSpecies {
+    Angry,
+    Spreader,
+    Sprinkler,
+    Burst { shots: usize },
+    Sniper { aim: Option<Vec2<f32>> },
+}
+
+impl Default for WeedSpecies {
+    fn default() -> Self {
+        Self::Angry
+    }
+}
+
+impl WeedSpecies {
+    pub const ALL: [WeedSpecies; 5] = [
+        WeedSpecies::Angry,
+        WeedSpecies::Spreader,
+        WeedSpecies::Sprinkler,
+        WeedSpecies::Burst { shots: 0 },
+        WeedSpecies::Sniper { aim: None },
+    ];
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Angry => "Angry",
+            Self::Spreader => "Spreader",
+            Self::Sprinkler => "Sprinkler",
+            Self::Burst { .. } => "Burst",
+            Self::Sniper { .. } => "Sniper",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|species| species.name() == name)
+    }
+    pub fn text(&self) -> &'static str {
+        match self {
+            Self::Angry => "tile.angry_weed",
+            Self::Spreader => "tile.spreader_weed",
+            Self::Sprinkler => "tile.sprinkler_weed",
+            Self::Burst { .. } => "tile.burst_weed",
+            Self::Sniper { .. } => "tile.sniper_weed",
+        }
+    }
+    pub fn shoot_time(&self) -> f32 {
+        match self {
+            Self::Angry => ANGRY_WEED_SHOOT_TIME,
+            Self::Spreader | Self::Sprinkler | Self::Burst { .. } => 4.0,
+            Self::Sniper { .. } => 5.0,
+        }
+    }
+    pub fn sprout(mutation: Option<Mutation>, rng: &mut StdRng) -> Self {
+        if mutation.is_none() {
+            return Self::Angry;
+        }
+        match rng.gen_range(0..7) {
+            0 => Self::Spreader,
+            1 => Self::Sprinkler,
+            2 => Self::Burst { shots: 0 },
+            3 => Self::Sniper { aim: None },
+            _ => Self::Angry,
+        }
+    }
+    pub fn killed_by(&self, mutation: Option<Mutation>, projectile: Option<Mutation>) -> bool {
+        match *self {
+            Self::Sprinkler => true,
+            Self::Burst { shots } => shots == 0 && mutation == projectile,
+            Self::Sniper { aim } => aim.is_some() || mutation == projectile,
+            Self::Angry | Self::Spreader => mutation == projectile,
+        }
+    }
+    pub fn update(
+        &mut self,
+        time: &mut f32,
+        pos: Vec2<f32>,
+        mutation: Option<Mutation>,
+        player: &Player,
+        rng: &mut StdRng,
+        projectiles: &mut Vec<Projectile>,
+        events: &mut Vec<GameEvent>,
+    ) {
+        if let Self::Sniper { aim } = self {
+            if *time < SNIPER_AIM_TIME && aim.is_none() {
+                *aim = Some(player.pos);
+            }
+        }
+        if *time >= 0.0 {
+            return;
+        }
+        *time = self.shoot_time();
+        let projectile =
+            |dir: Vec2<f32>, speed: f32| Projectile::new(pos, 0.2, dir * speed, mutation);
+        let mut shoot = |projectile: Projectile| {
+            events.push(GameEvent::ProjectileFired { pos, mutation });
+            projectiles.push(projectile);
+        };
+        let to_player = player.pos - pos;
+        match self {
+            Self::Angry => {
+                if to_player.len() > 1e-5 {
+                    shoot(projectile(to_player.normalize(), 3.0));
+                }
+            }
+            Self::Spreader => {
+                if to_player.len() > 1e-5 {
+                    let count = rng.gen_range(3..=5);
+                    for i in 0..count {
+                        let angle = SPREADER_SPREAD * (i as f32 / (count - 1) as f32 - 0.5);
+                        shoot(
+                            projectile(Vec2::rotated(to_player.normalize(), angle), 3.0)
+                                .with_behaviour(Behaviour::Bouncing { bounces: 1 }),
+                        );
+                    }
+                }
+            }
+            Self::Sprinkler => {
+                let offset = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
+                for i in 0..SPRINKLER_SHOTS {
+                    let angle =
+                        offset + 2.0 * std::f32::consts::PI * i as f32 / SPRINKLER_SHOTS as f32;
+                    shoot(
+                        projectile(Vec2::rotated(vec2(1.0, 0.0), angle), 3.0)
+                            .with_behaviour(Behaviour::Mine { drag: 1.5 })
+                            .with_lifetime(MINE_LIFETIME),
+                    );
+                }
+            }
+            Self::Burst { shots } => {
+                if *shots == 0 {
+                    *shots = BURST_SHOTS;
+                }
+                *shots -= 1;
+                if *shots > 0 {
+                    *time = BURST_INTERVAL;
+                }
+                if to_player.len() > 1e-5 {
+                    shoot(
+                        projectile(to_player.normalize(), 4.0)
+                            .with_behaviour(Behaviour::Homing { turn_rate: 1.5 })
+                            .with_lifetime(HOMING_LIFETIME),
+                    );
+                }
+            }
+            Self::Sniper { aim } => {
+                if let Some(aim) = aim.take() {
+                    if (aim - pos).len() > 1e-5 {
+                        shoot(projectile((aim - pos).normalize(), 9.0));
+                    }
+                }
+            }
+        }
+    }
+}